@@ -1,5 +1,7 @@
 // use crate::{Command, Interface, Response, Result};
 
+pub mod known;
+
 /// Error returned when the [Aid::try_new](Aid::try_new) or
 /// [Aid::try_new_truncatable](Aid::try_new_truncatable) fail
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -23,6 +25,17 @@ impl core::fmt::Debug for FromSliceError {
     }
 }
 
+/// Error returned by [Aid::from_hex](Aid::from_hex)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FromHexError {
+    /// A character is neither a hex digit nor a separator
+    InvalidCharacter,
+    /// The hex digits do not form whole bytes
+    OddLength,
+    /// The decoded bytes are not a valid AID
+    InvalidAid(FromSliceError),
+}
+
 #[derive(Copy, Clone, Eq, Hash, PartialEq, PartialOrd, Ord)]
 /// ISO 7816-4 Application identifier
 pub struct Aid {
@@ -38,7 +51,7 @@ pub struct Aid {
     truncated_len: u8,
 }
 
-#[derive(Copy, Clone, Eq, Hash, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 pub enum Category {
     /// International registration of application providers according to ISO/IEC 7816-5
     International,
@@ -81,6 +94,7 @@ impl core::fmt::Debug for Aid {
 ///  a single AID in the proprietary, national or international category, and/or
 ///  one or more AIDs in the standard category.
 
+#[allow(clippy::empty_line_after_doc_comments)]
 pub trait App {
     // using an associated constant here would make the trait object unsafe
     fn aid(&self) -> Aid;
@@ -97,7 +111,8 @@ impl core::ops::Deref for Aid {
 }
 
 impl Aid {
-    const MAX_LEN: usize = 16;
+    /// Maximum length of an AID in bytes, as defined in ISO 7816-4
+    pub const MAX_LEN: usize = 16;
 
     pub fn as_bytes(&self) -> &[u8] {
         &self.bytes[..self.len as usize]
@@ -155,15 +170,64 @@ impl Aid {
             truncated_len: truncated_len as u8,
         };
         s = s.fill(aid, 0);
-        if s.is_national() && aid.len() >= 5 {
+        if s.is_national() && aid.len() < 5 {
             return Err(FromSliceError::NationalRidTooShort);
         }
-        if s.is_international() && aid.len() >= 5 {
+        if s.is_international() && aid.len() < 5 {
             return Err(FromSliceError::InternationalRidTooShort);
         }
         Ok(s)
     }
 
+    /// Parse an Aid from hex digits, optionally separated by spaces or colons between bytes
+    ///
+    /// ```
+    /// # use iso7816::Aid;
+    /// let aid = Aid::from_hex("A0:00:00:03:08 00001000 0100").unwrap();
+    /// assert_eq!(aid.as_bytes(), &[0xA0, 0, 0, 3, 8, 0, 0, 0x10, 0, 1, 0]);
+    /// ```
+    pub const fn from_hex(hex: &str) -> Result<Self, FromHexError> {
+        let hex = hex.as_bytes();
+        let mut bytes = [0u8; Self::MAX_LEN];
+        let mut len = 0;
+        let mut high = None;
+        let mut i = 0;
+        while i < hex.len() {
+            let nibble = match hex[i] {
+                b' ' | b':' => {
+                    if high.is_some() {
+                        return Err(FromHexError::OddLength);
+                    }
+                    i += 1;
+                    continue;
+                }
+                c @ b'0'..=b'9' => c - b'0',
+                c @ b'a'..=b'f' => c - b'a' + 10,
+                c @ b'A'..=b'F' => c - b'A' + 10,
+                _ => return Err(FromHexError::InvalidCharacter),
+            };
+            match high {
+                None => high = Some(nibble),
+                Some(high_nibble) => {
+                    if len == Self::MAX_LEN {
+                        return Err(FromHexError::InvalidAid(FromSliceError::TooLong));
+                    }
+                    bytes[len] = (high_nibble << 4) | nibble;
+                    len += 1;
+                    high = None;
+                }
+            }
+            i += 1;
+        }
+        if high.is_some() {
+            return Err(FromHexError::OddLength);
+        }
+        match Self::try_new(bytes.split_at(len).0) {
+            Ok(aid) => Ok(aid),
+            Err(err) => Err(FromHexError::InvalidAid(err)),
+        }
+    }
+
     // workaround to copy in the aid while remaining "const"
     // maybe there is a better way?
     const fn fill(mut self, bytes: &[u8], i: usize) -> Self {
@@ -178,10 +242,10 @@ impl Aid {
 
     pub const fn category(&self) -> Category {
         match self.bytes[0] >> 4 {
-            b'A' => Category::International,
-            b'D' => Category::National,
-            b'E' => Category::Standard,
-            b'F' => Category::Proprietary,
+            0xA => Category::International,
+            0xD => Category::National,
+            0xE => Category::Standard,
+            0xF => Category::Proprietary,
             _ => Category::Other,
         }
     }
@@ -214,15 +278,27 @@ impl Aid {
     }
 
     /// Proprietary application identifier extension, up to 11 bytes.
+    ///
+    /// This is the remainder of the AID after the [RID](Aid::rid), `None` if the AID has no RID.
     pub fn pix(&self) -> Option<&[u8]> {
-        self.has_rid_pix().then(|| &self.bytes[5..])
+        self.has_rid_pix()
+            .then(|| &self.bytes[5..self.len as usize])
+    }
+
+    /// Returns `true` if the AID begins with `prefix`
+    ///
+    /// This is the partial matching used when a SELECT command contains a truncated AID: the
+    /// card selects the first application whose AID starts with the data field.
+    pub fn starts_with(&self, prefix: &[u8]) -> bool {
+        self.as_bytes().starts_with(prefix)
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::Aid;
+    use super::{Aid, Category, FromHexError, FromSliceError};
     use hex_literal::hex;
+    const PIV_AID_FULL: Aid = Aid::new(&hex!("A000000308 00001000 0100"));
     #[allow(dead_code)]
     const PIV_AID: Aid = Aid::new_truncatable(&hex!("A000000308 00001000 0100"), 9);
 
@@ -234,4 +310,65 @@ mod test {
         // panics
         // let aid = Aid::new(&hex_literal::hex!("A000000308 00001000 01001232323333333333333332"));
     }
+
+    #[test]
+    fn max_len() {
+        let bytes = [0xF0; Aid::MAX_LEN + 1];
+        assert!(Aid::try_new(&bytes[..Aid::MAX_LEN]).is_ok());
+        assert_eq!(Aid::try_new(&bytes), Err(FromSliceError::TooLong));
+    }
+
+    #[test]
+    fn from_hex() {
+        assert_eq!(Aid::from_hex("A0000003080000100001 00"), Ok(PIV_AID_FULL));
+        assert_eq!(
+            Aid::from_hex("a0:00:00:03:08:00:00:10:00:01:00"),
+            Ok(PIV_AID_FULL)
+        );
+        assert_eq!(Aid::from_hex("A00"), Err(FromHexError::OddLength));
+        assert_eq!(Aid::from_hex("A 0"), Err(FromHexError::OddLength));
+        assert_eq!(Aid::from_hex("A0-00"), Err(FromHexError::InvalidCharacter));
+        assert_eq!(
+            Aid::from_hex(""),
+            Err(FromHexError::InvalidAid(FromSliceError::Empty))
+        );
+        assert_eq!(
+            Aid::from_hex("000102030405060708090A0B0C0D0E0F10"),
+            Err(FromHexError::InvalidAid(FromSliceError::TooLong))
+        );
+    }
+
+    #[test]
+    fn rid_pix() {
+        assert_eq!(PIV_AID_FULL.category(), Category::International);
+        assert_eq!(PIV_AID_FULL.rid(), Some(&hex!("A000000308")[..]));
+        assert_eq!(PIV_AID_FULL.pix(), Some(&hex!("00001000 0100")[..]));
+
+        let openpgp = Aid::new(&hex!("D276000124 01"));
+        assert_eq!(openpgp.category(), Category::National);
+        assert_eq!(openpgp.rid(), Some(&hex!("D276000124")[..]));
+        assert_eq!(openpgp.pix(), Some(&hex!("01")[..]));
+
+        let proprietary = Aid::new(&hex!("F0 0102"));
+        assert_eq!(proprietary.category(), Category::Proprietary);
+        assert_eq!(proprietary.rid(), None);
+        assert_eq!(proprietary.pix(), None);
+
+        assert_eq!(
+            Aid::try_new(&hex!("A00000")),
+            Err(FromSliceError::InternationalRidTooShort)
+        );
+        assert_eq!(
+            Aid::try_new(&hex!("D27600")),
+            Err(FromSliceError::NationalRidTooShort)
+        );
+    }
+
+    #[test]
+    fn starts_with() {
+        assert!(PIV_AID_FULL.starts_with(&hex!("A000000308")));
+        assert!(PIV_AID_FULL.starts_with(&hex!("A000000308 00001000 0100")));
+        assert!(!PIV_AID_FULL.starts_with(&hex!("A000000308 00001000 0100 00")));
+        assert!(!PIV_AID_FULL.starts_with(&hex!("A000000309")));
+    }
 }
@@ -0,0 +1,42 @@
+//! Application identifiers of well-known card applications
+
+use super::Aid;
+
+/// PIV card application (NIST SP 800-73-4), version 1.0
+///
+/// Truncated to `A0 00 00 03 08 00 00 10 00` when matching SELECT commands.
+pub const PIV: Aid = Aid::new_truncatable(
+    &[
+        0xA0, 0x00, 0x00, 0x03, 0x08, 0x00, 0x00, 0x10, 0x00, 0x01, 0x00,
+    ],
+    9,
+);
+
+/// OpenPGP card application, without version, manufacturer and serial number
+pub const OPENPGP: Aid = Aid::new(&[0xD2, 0x76, 0x00, 0x01, 0x24, 0x01]);
+
+/// FIDO U2F and CTAP2 applet
+pub const FIDO: Aid = Aid::new(&[0xA0, 0x00, 0x00, 0x06, 0x47, 0x2F, 0x00, 0x01]);
+
+/// NFC Forum Type 4 Tag NDEF application, version 2.0
+pub const NDEF: Aid = Aid::new(&[0xD2, 0x76, 0x00, 0x00, 0x85, 0x01, 0x01]);
+
+/// GlobalPlatform issuer security domain
+pub const GLOBAL_PLATFORM_ISD: Aid = Aid::new(&[0xA0, 0x00, 0x00, 0x01, 0x51, 0x00, 0x00, 0x00]);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn known() {
+        assert_eq!(PIV.as_bytes(), hex!("A000000308 00001000 0100"));
+        assert!(PIV.matches(&hex!("A000000308 00001000 0100")));
+        assert!(PIV.matches(&hex!("A000000308 00001000")));
+        assert!(OPENPGP.matches(&hex!("D276000124 01 0304 000A 00000001 0000")));
+        assert_eq!(FIDO.as_bytes(), hex!("A000000647 2F0001"));
+        assert_eq!(NDEF.as_bytes(), hex!("D276000085 0101"));
+        assert_eq!(GLOBAL_PLATFORM_ISD.as_bytes(), hex!("A000000151 000000"));
+    }
+}
@@ -0,0 +1,199 @@
+//! Card capabilities, as advertised in the historical bytes of the ATR (ISO 7816-4 12.1.1)
+//!
+//! The result can be fed into the `supports_extended` argument of
+//! [`CommandBuilder::serialize_into`](crate::CommandBuilder::serialize_into):
+//!
+//! ```
+//! # use iso7816::capabilities::CardCapabilities;
+//! // Category indicator 80, card capabilities 73 with extended Lc and Le fields
+//! let historical_bytes = [0x80, 0x73, 0x00, 0x00, 0xC0];
+//! let capabilities = CardCapabilities::from_historical_bytes(&historical_bytes).unwrap();
+//! assert!(capabilities.supports_extended_length());
+//! assert!(capabilities.supports_command_chaining());
+//! ```
+
+/// BER-TLV tag of the card capabilities data object, which is found in EF.ATR/INFO
+pub const TAG: u8 = 0x47;
+
+/// COMPACT-TLV tag of the card capabilities in the historical bytes
+const COMPACT_TAG: u8 = 0x7;
+
+/// Card capabilities data object (tag `47`, or `7X` in the historical bytes)
+///
+/// The data object consists of up to three software function tables. Missing tables are
+/// treated as zero, i.e. no capability is advertised.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct CardCapabilities {
+    /// First software function table: selection methods
+    pub selection_methods: u8,
+    /// Second software function table: data coding byte
+    pub data_coding: u8,
+    /// Third software function table: command chaining, length fields and logical channels
+    pub functions: u8,
+}
+
+impl CardCapabilities {
+    const COMMAND_CHAINING: u8 = 1 << 7;
+    const EXTENDED_LENGTH: u8 = 1 << 6;
+    const EXTENDED_LENGTH_INFO: u8 = 1 << 5;
+
+    /// Parse the value of the card capabilities data object
+    ///
+    /// Returns `None` if `value` is longer than three bytes.
+    pub const fn from_value(value: &[u8]) -> Option<Self> {
+        let mut tables = [0; 3];
+        if value.len() > tables.len() {
+            return None;
+        }
+        let mut i = 0;
+        while i < value.len() {
+            tables[i] = value[i];
+            i += 1;
+        }
+        Some(Self {
+            selection_methods: tables[0],
+            data_coding: tables[1],
+            functions: tables[2],
+        })
+    }
+
+    /// Find and parse the card capabilities in the historical bytes of an ATR
+    ///
+    /// Only the category indicators `00` and `80`, which use COMPACT-TLV data objects, are
+    /// supported. Returns `None` if the historical bytes are malformed or do not contain card
+    /// capabilities.
+    pub fn from_historical_bytes(historical_bytes: &[u8]) -> Option<Self> {
+        let (&category, objects) = historical_bytes.split_first()?;
+        let mut objects = match category {
+            // the last three bytes are the mandatory status indicator
+            0x00 => &objects[..objects.len().checked_sub(3)?],
+            0x80 => objects,
+            _ => return None,
+        };
+        while let Some((&tag_len, rest)) = objects.split_first() {
+            let len = usize::from(tag_len & 0x0F);
+            if rest.len() < len {
+                return None;
+            }
+            let (value, rest) = rest.split_at(len);
+            if tag_len >> 4 == COMPACT_TAG {
+                return Self::from_value(value);
+            }
+            objects = rest;
+        }
+        None
+    }
+
+    /// Returns `true` if the card supports command chaining
+    pub const fn supports_command_chaining(&self) -> bool {
+        self.functions & Self::COMMAND_CHAINING != 0
+    }
+
+    /// Returns `true` if the card supports extended Lc and Le fields
+    pub const fn supports_extended_length(&self) -> bool {
+        self.functions & Self::EXTENDED_LENGTH != 0
+    }
+
+    /// Returns `true` if the maximum lengths are given in the extended length information
+    /// data object (tag `7F66`) of EF.ATR/INFO
+    pub const fn has_extended_length_info(&self) -> bool {
+        self.functions & Self::EXTENDED_LENGTH_INFO != 0
+    }
+
+    /// Maximum number of logical channels, including the basic channel
+    ///
+    /// Only meaningful if the card supports logical channels (bits 5 and 4 of the third
+    /// table); `8` means eight or more.
+    pub const fn max_logical_channels(&self) -> u8 {
+        (self.functions & 0x07) + 1
+    }
+}
+
+/// Returns `true` if the historical bytes advertise support for extended Lc and Le fields
+///
+/// Cards without card capabilities in their historical bytes are assumed to only support
+/// short APDUs.
+pub fn supports_extended_length(historical_bytes: &[u8]) -> bool {
+    CardCapabilities::from_historical_bytes(historical_bytes)
+        .map(|capabilities| capabilities.supports_extended_length())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn historical_bytes() {
+        // category 80, card capabilities 73, followed by another data object
+        let historical = hex!("80 73 C0 21 C0 57 59 75 62 69 4B 65 79");
+        let capabilities = CardCapabilities::from_historical_bytes(&historical).unwrap();
+        assert_eq!(
+            capabilities,
+            CardCapabilities {
+                selection_methods: 0xC0,
+                data_coding: 0x21,
+                functions: 0xC0,
+            }
+        );
+        assert!(capabilities.supports_extended_length());
+        assert!(capabilities.supports_command_chaining());
+        assert!(!capabilities.has_extended_length_info());
+        assert_eq!(capabilities.max_logical_channels(), 1);
+        assert!(supports_extended_length(&historical));
+
+        // category 00: the last three bytes are the status indicator, not a data object
+        let short = hex!("00 31 C0 72 F8 00 00 90 00");
+        let capabilities = CardCapabilities::from_historical_bytes(&short).unwrap();
+        assert_eq!(capabilities.selection_methods, 0xF8);
+        assert!(!capabilities.supports_extended_length());
+        assert!(!supports_extended_length(&short));
+        assert_eq!(
+            CardCapabilities::from_historical_bytes(&hex!("00 31 C0 73 00 00")),
+            None
+        );
+
+        // no card capabilities, truncated or unsupported category
+        assert_eq!(
+            CardCapabilities::from_historical_bytes(&hex!("80 31 C0")),
+            None
+        );
+        assert_eq!(
+            CardCapabilities::from_historical_bytes(&hex!("80 73 00 00")),
+            None
+        );
+        assert_eq!(
+            CardCapabilities::from_historical_bytes(&hex!("10 73 00 00 C0")),
+            None
+        );
+        assert_eq!(CardCapabilities::from_historical_bytes(&[]), None);
+        assert!(!supports_extended_length(&[]));
+    }
+
+    #[test]
+    fn from_value() {
+        assert_eq!(
+            CardCapabilities::from_value(&hex!("00 00 47")),
+            Some(CardCapabilities {
+                selection_methods: 0,
+                data_coding: 0,
+                functions: 0x47,
+            })
+        );
+        assert_eq!(
+            CardCapabilities::from_value(&hex!("F8")),
+            Some(CardCapabilities {
+                selection_methods: 0xF8,
+                ..Default::default()
+            })
+        );
+        assert_eq!(CardCapabilities::from_value(&hex!("00 00 00 00")), None);
+
+        let capabilities = CardCapabilities::from_value(&hex!("00 00 67")).unwrap();
+        assert!(capabilities.supports_extended_length());
+        assert!(capabilities.has_extended_length_info());
+        assert!(!capabilities.supports_command_chaining());
+        assert_eq!(capabilities.max_logical_channels(), 8);
+    }
+}
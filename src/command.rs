@@ -1,10 +1,85 @@
+use crate::writer::{BufferFull, HexWriter, Writer};
 use crate::Data;
 
+mod builder;
 pub mod class;
+mod disassembly;
 pub mod instruction;
+pub mod security;
+pub use builder::{serialize_batch, Chunks, CommandBuilder, SerializeState};
+pub use disassembly::Disassembly;
 pub use instruction::Instruction;
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+/// Command APDU cases, cf. ISO 7816-3, 12.1.2
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Case {
+    /// No command data, no response data expected
+    Case1,
+    /// No command data, response data expected
+    Case2,
+    /// Command data, no response data expected
+    Case3,
+    /// Command data, response data expected
+    Case4,
+}
+
+impl Case {
+    /// `le` follows the convention of [`Command::expected`]: `0` means no Le field.
+    pub(crate) fn new(lc: usize, le: usize) -> Self {
+        match (lc, le) {
+            (0, 0) => Self::Case1,
+            (0, _) => Self::Case2,
+            (_, 0) => Self::Case3,
+            (_, _) => Self::Case4,
+        }
+    }
+}
+
+/// Le field of a command APDU, i.e. the maximum number of expected response bytes
+///
+/// This makes the encoding of Le explicit: an absent field, which means that no response data
+/// is expected, is distinct from the field `00`, which requests as many bytes as possible.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Le {
+    /// No Le field: no response data is expected
+    None,
+    /// Up to 65536 bytes, encoded as `0000` in an extended APDU
+    ///
+    /// The short encoding `00` requests up to 256 bytes, which is `Exact(256)`.
+    Max,
+    /// Up to the given number of bytes, where `Exact(0)` is the same as [`None`](Self::None)
+    Exact(u16),
+}
+
+impl Le {
+    /// Decoded length, following the convention of [`Command::expected`]: `0` for no Le field,
+    /// 65536 for [`Max`](Self::Max)
+    pub const fn expected(self) -> usize {
+        match self {
+            Self::None => 0,
+            Self::Max => 65_536,
+            Self::Exact(le) => le as usize,
+        }
+    }
+
+    /// Inverse of [`expected`](Self::expected), saturating at [`Max`](Self::Max)
+    pub const fn from_expected(le: usize) -> Self {
+        match le {
+            0 => Self::None,
+            1..=65_535 => Self::Exact(le as u16),
+            _ => Self::Max,
+        }
+    }
+}
+
+impl From<u16> for Le {
+    fn from(le: u16) -> Self {
+        Self::Exact(le)
+    }
+}
+
+#[derive(Clone, Debug, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Command<const S: usize> {
     class: class::Class,
     instruction: Instruction,
@@ -20,8 +95,12 @@ pub struct Command<const S: usize> {
     pub extended: bool,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, Eq)]
 /// Memory-efficient unowned version of [`Command`]
+///
+/// The header is available through [`class`](Self::class), [`instruction`](Self::instruction)
+/// and the public `p1` and `p2` fields. All of them are decoded once when parsing, so accessing
+/// them does not re-parse the APDU.
 pub struct CommandView<'a> {
     class: class::Class,
     instruction: Instruction,
@@ -67,13 +146,129 @@ impl<'a> CommandView<'a> {
         self.data
     }
 
+    /// Maximum number of expected response bytes (Ne), `0` if the Le field is absent
+    ///
+    /// An encoded Le of `0` is decoded to 256 for short and 65536 for extended APDUs, so this
+    /// can be used directly to size the response buffer. Use [`le`](Self::le) for the raw value.
     pub fn expected(&self) -> usize {
         self.le
     }
+
+    /// Length of the command data (Nc)
+    pub fn lc(&self) -> usize {
+        self.data.len()
+    }
+
+    /// P1 and P2 as one big endian value, e.g. an offset or a tag
+    pub fn p12(&self) -> u16 {
+        u16::from_be_bytes([self.p1, self.p2])
+    }
+
+    /// Le field of the APDU
+    ///
+    /// As per ISO 7816-4, `00` (short) encodes 256 and is returned as `Le::Exact(256)`, while
+    /// `0000` (extended) encodes 65536 and is returned as [`Le::Max`]. Passing the result to a
+    /// constructor yields a command with the same [`expected`](Self::expected) length.
+    pub fn le(&self) -> Le {
+        Le::from_expected(self.le)
+    }
+
+    /// Returns `true` if the Lc field is present and uses the extended (three byte) encoding
+    ///
+    /// ISO 7816-3 does not allow mixing short and extended fields, so this only differs from
+    /// [`le_extended`](Self::le_extended) if one of the fields is absent.
+    pub fn lc_extended(&self) -> bool {
+        self.extended && !self.data.is_empty()
+    }
+
+    /// Returns `true` if the Le field is present and uses the extended encoding
+    pub fn le_extended(&self) -> bool {
+        self.extended && self.le != 0
+    }
+
+    pub fn case(&self) -> Case {
+        Case::new(self.data.len(), self.le)
+    }
+
+    /// Returns `true` if the chaining bit is set in CLA, i.e. more commands of the chain follow
+    pub fn is_chained(&self) -> bool {
+        self.class.chain().not_the_last()
+    }
+
+    /// Write the APDU into `writer` as space-separated uppercase hex, e.g. `00 A4 04 00 00`
+    ///
+    /// The length fields keep their short or extended encoding. This is meant for logging
+    /// where `format!` is not available.
+    pub fn write_hex<W: Writer>(&self, writer: &mut W) -> Result<(), W::Error> {
+        CommandBuilder::from(self.clone()).write_apdu(self.extended, &mut HexWriter::new(writer))
+    }
 }
 
 impl<const S: usize> Command<S> {
-    pub fn try_from(apdu: &[u8]) -> Result<Self, FromSliceError> {
+    /// Maximum length of the data, `S`
+    pub const CAPACITY: usize = S;
+
+    /// Returns `true` if `data_len` bytes of data fit into the command
+    ///
+    /// This can be checked at compile time, also in generic code:
+    ///
+    /// ```
+    /// # use iso7816::Command;
+    /// fn handle<const S: usize>(command: &Command<S>) {
+    ///     const { assert!(Command::<S>::fits(255)) };
+    /// }
+    /// ```
+    ///
+    /// See also [`assert_command_capacity!`](crate::assert_command_capacity).
+    pub const fn fits(data_len: usize) -> bool {
+        data_len <= S
+    }
+
+    /// Create a command, copying `data`
+    ///
+    /// `le` is the maximum number of expected response bytes, see [`Le`]. `extended` is set if
+    /// the data or Le do not fit into a short APDU.
+    ///
+    /// This method panics if `data` does not fit into `S` bytes. For a similar method
+    /// returning a result instead, use [try_new](Command::try_new).
+    pub fn new(
+        class: class::Class,
+        instruction: Instruction,
+        p1: u8,
+        p2: u8,
+        data: &[u8],
+        le: impl Into<Le>,
+    ) -> Self {
+        match Self::try_new(class, instruction, p1, p2, data, le) {
+            Ok(command) => command,
+            Err(_e) => panic!("Command data too long"),
+        }
+    }
+
+    /// Create a command, copying `data`
+    ///
+    /// Fails with [`Error::BufferOverflow`] if `data` does not fit into `S` bytes.
+    pub fn try_new(
+        class: class::Class,
+        instruction: Instruction,
+        p1: u8,
+        p2: u8,
+        data: &[u8],
+        le: impl Into<Le>,
+    ) -> Result<Self, Error> {
+        let le = le.into().expected();
+        Ok(Self {
+            class,
+            instruction,
+            p1,
+            p2,
+            data: to_data(data)?,
+            le,
+            extended: data.len() > 255 || le > 256,
+        })
+    }
+
+    pub fn try_from(apdu: &[u8]) -> Result<Self, Error> {
         apdu.try_into()
     }
 
@@ -89,14 +284,64 @@ impl<const S: usize> Command<S> {
         &self.data
     }
 
+    /// Mutable access to the data, for example to encrypt it in place
     pub fn data_mut(&mut self) -> &mut Data<S> {
         &mut self.data
     }
 
+    /// Replace the data
+    ///
+    /// Fails with [`Error::BufferOverflow`] if `data` does not fit into `S` bytes, in
+    /// which case the command is left unchanged.
+    pub fn set_data(&mut self, data: &[u8]) -> Result<(), Error> {
+        self.data = to_data(data)?;
+        Ok(())
+    }
+
+    /// Maximum number of expected response bytes (Ne), `0` if the Le field is absent
+    ///
+    /// An encoded Le of `0` is decoded to 256 for short and 65536 for extended APDUs, so this
+    /// can be used directly to size the response buffer. Use [`le`](Self::le) for the raw value.
     pub fn expected(&self) -> usize {
         self.le
     }
 
+    /// Length of the command data (Nc)
+    pub fn lc(&self) -> usize {
+        self.data.len()
+    }
+
+    /// P1 and P2 as one big endian value, e.g. an offset or a tag
+    pub fn p12(&self) -> u16 {
+        u16::from_be_bytes([self.p1, self.p2])
+    }
+
+    /// Le field of the APDU
+    ///
+    /// As per ISO 7816-4, `00` (short) encodes 256 and is returned as `Le::Exact(256)`, while
+    /// `0000` (extended) encodes 65536 and is returned as [`Le::Max`]. Passing the result to a
+    /// constructor yields a command with the same [`expected`](Self::expected) length.
+    pub fn le(&self) -> Le {
+        Le::from_expected(self.le)
+    }
+
+    /// Returns `true` if the Lc field is present and uses the extended (three byte) encoding
+    ///
+    /// ISO 7816-3 does not allow mixing short and extended fields, so this only differs from
+    /// [`le_extended`](Self::le_extended) if one of the fields is absent.
+    pub fn lc_extended(&self) -> bool {
+        self.extended && !self.data.is_empty()
+    }
+
+    /// Returns `true` if the Le field is present and uses the extended encoding
+    pub fn le_extended(&self) -> bool {
+        self.extended && self.le != 0
+    }
+
+    pub fn case(&self) -> Case {
+        Case::new(self.data.len(), self.le)
+    }
+
     /// This can be use for APDU chaining to convert
     /// multiple APDU's into one.
     /// * Global Platform GPC_SPE_055 3.10
@@ -104,6 +349,18 @@ impl<const S: usize> Command<S> {
     pub fn extend_from_command<const T: usize>(
         &mut self,
         command: &Command<T>,
+    ) -> core::result::Result<(), ()> {
+        self.extend_from_command_view(&command.as_view())
+    }
+
+    /// Same as [`extend_from_command`](Self::extend_from_command), for a [`CommandView`]
+    ///
+    /// Once the last command of the chain has been added, [`is_chained`](Self::is_chained)
+    /// returns `false`, as the header is taken from the last command.
+    #[allow(clippy::result_unit_err)]
+    pub fn extend_from_command_view(
+        &mut self,
+        command: &CommandView<'_>,
     ) -> core::result::Result<(), ()> {
         // Always take the header from the last command;
         self.class = command.class();
@@ -116,28 +373,162 @@ impl<const S: usize> Command<S> {
         // add the data to the end.
         self.data.extend_from_slice(command.data())
     }
+
+    /// Returns `true` if the chaining bit is set in CLA, i.e. more commands of the chain follow
+    pub fn is_chained(&self) -> bool {
+        self.class.chain().not_the_last()
+    }
+
+    /// Clear the chaining bit in CLA
+    pub fn clear_chaining(&mut self) {
+        // clearing the bit never yields the invalid class 0xFF
+        if let Ok(class) = self.class.with_chaining(false) {
+            self.class = class;
+        }
+    }
+
+    /// Compare with `other` like `==`, but including the command chaining bit of the class and
+    /// the length encoding
+    ///
+    /// Equality between [`Command`], [`CommandView`] and [`CommandBuilder`] ignores the chaining
+    /// bit, so that a reassembled chain equals the original command, and whether the command
+    /// uses short or extended length fields.
+    pub fn bitwise_eq(&self, other: &CommandView<'_>) -> bool {
+        self.class == other.class() && self.extended == other.extended && self == other
+    }
+
+    /// Describe the command field by field, for debugging, see [`CommandView::disassemble`]
+    pub fn disassemble(&self) -> Disassembly<'_> {
+        self.as_view().disassemble()
+    }
+
+    pub fn as_view(&self) -> CommandView<'_> {
+        CommandView {
+            class: self.class,
+            instruction: self.instruction,
+            p1: self.p1,
+            p2: self.p2,
+            data: &self.data,
+            le: self.le,
+            extended: self.extended,
+        }
+    }
+
+    /// Serialize the command into `writer`, as [`CommandBuilder::serialize_into`] does
+    ///
+    /// The length fields are chosen according to `supports_extended`, not copied from the
+    /// parsed APDU. To force an encoding, convert [`as_view`](Self::as_view) into a
+    /// [`CommandBuilder`] and use [`CommandBuilder::encode_short`] or
+    /// [`CommandBuilder::encode_extended`].
+    pub fn serialize_into<W: Writer>(
+        &self,
+        writer: &mut W,
+        supports_extended: bool,
+    ) -> Result<Result<usize, CommandBuilder<&[u8]>>, W::Error> {
+        CommandBuilder::from(self.as_view()).serialize_borrowed(writer, supports_extended)
+    }
+
+    /// Serialize the command into a `heapless::Vec` of capacity `N`, see
+    /// [`CommandBuilder::serialize_to_heapless`]
+    pub fn serialize_to_heapless<const N: usize>(
+        &self,
+        supports_extended: bool,
+    ) -> Result<heapless::Vec<u8, N>, BufferFull> {
+        CommandBuilder::from(self.as_view()).serialize_to_heapless(supports_extended)
+    }
+
+    /// Move the command into a command with a capacity of `T` bytes
+    ///
+    /// Returns the original command if its data does not fit into `T` bytes.
+    pub fn try_into_capacity<const T: usize>(self) -> Result<Command<T>, Self> {
+        match Data::from_slice(&self.data) {
+            Ok(data) => Ok(Command {
+                class: self.class,
+                instruction: self.instruction,
+                p1: self.p1,
+                p2: self.p2,
+                data,
+                le: self.le,
+                extended: self.extended,
+            }),
+            Err(()) => Err(self),
+        }
+    }
 }
 
+/// Error returned by the fallible functions of this module
+///
+/// The serialization of commands is the exception: it fails with the error of the
+/// [`Writer`], or with [`BufferFull`] for [`CommandBuilder::serialize_to_heapless`] and
+/// [`CommandBuilder::chunks`], just like writing into a full buffer.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub enum FromSliceError {
+#[non_exhaustive]
+pub enum Error {
+    /// The APDU is shorter than the four header bytes
     TooShort,
-    TooLong,
+    /// The APDU is valid, but its data does not fit into the capacity of the [`Command`]
+    BufferOverflow { needed: usize, capacity: usize },
+    /// The class byte is `0xFF`
     InvalidClass,
+    /// The body is too long for a short APDU, but does not start with the extended marker `00`
     InvalidFirstBodyByteForExtended,
+    /// The body length matches neither the short nor the extended encoding of Lc and Le
     InvalidSliceLength,
+    /// Lc announces more data bytes than the APDU contains
+    LcMismatch,
+    /// The APDU continues after the data and Le field
+    TrailingBytes,
+    /// The offset of a READ BINARY or UPDATE BINARY command does not fit into 15 bits
+    InvalidOffset,
+    /// The APDU uses extended length fields, but the short encoding would have sufficed
+    NonCanonical,
+    /// The command needs an extended Lc or Le field, but the card only supports short APDUs
+    ExtendedRequired,
+}
+
+/// Former name of [`Error`], which was only used for parsing
+pub type FromSliceError = Error;
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::TooShort => f.write_str("command APDU shorter than its header"),
+            Self::BufferOverflow { needed, capacity } => write!(
+                f,
+                "command data of {needed} bytes exceeds the capacity of {capacity} bytes"
+            ),
+            Self::InvalidClass => f.write_str("invalid class byte FF"),
+            Self::InvalidFirstBodyByteForExtended => f.write_str("invalid extended length marker"),
+            Self::InvalidSliceLength => f.write_str("invalid length fields"),
+            Self::LcMismatch => f.write_str("Lc larger than the data"),
+            Self::TrailingBytes => f.write_str("trailing bytes after Le"),
+            Self::InvalidOffset => f.write_str("offset does not fit into 15 bits"),
+            Self::NonCanonical => f.write_str("unnecessary extended length fields"),
+            Self::ExtendedRequired => f.write_str("extended length fields required"),
+        }
+    }
 }
 
-impl From<class::InvalidClass> for FromSliceError {
+impl core::error::Error for Error {}
+
+impl From<class::InvalidClass> for Error {
     fn from(_: class::InvalidClass) -> Self {
         Self::InvalidClass
     }
 }
 
+/// Parse a single command APDU, which must span the whole slice
+///
+/// Command APDUs are not self-delimiting: the length of the body is only known from the
+/// surrounding transport. For example, `00 B0 00 00 02 01 02` is both an APDU with Lc = 2 and the
+/// APDU `00 B0 00 00 02` (Le = 2) followed by the first bytes of another APDU. A sequence of
+/// concatenated APDUs therefore cannot be split reliably: frame it before parsing, or use the
+/// heuristic [`CommandView::parse_stream`].
 impl<'a> TryFrom<&'a [u8]> for CommandView<'a> {
-    type Error = FromSliceError;
+    type Error = Error;
     fn try_from(apdu: &'a [u8]) -> core::result::Result<Self, Self::Error> {
         if apdu.len() < 4 {
-            return Err(FromSliceError::TooShort);
+            return Err(Error::TooShort);
         }
         #[cfg(test)]
         println!("{}", apdu.len());
@@ -164,8 +555,57 @@ impl<'a> TryFrom<&'a [u8]> for CommandView<'a> {
     }
 }
 
+impl<'a> From<CommandView<'a>> for CommandBuilder<&'a [u8]> {
+    fn from(view: CommandView<'a>) -> Self {
+        Self::new(
+            view.class,
+            view.instruction,
+            view.p1,
+            view.p2,
+            view.data,
+            Le::from_expected(view.le),
+        )
+    }
+}
+
 impl<'a> CommandView<'a> {
-    pub fn to_owned<const S: usize>(&self) -> Result<Command<S>, FromSliceError> {
+    /// Parse a single command APDU like [`TryFrom`], but reject non-canonical length encodings
+    ///
+    /// Fails with [`Error::NonCanonical`] if the APDU uses extended length fields although the
+    /// data is at most 255 bytes long and at most 256 response bytes are expected, i.e. if the
+    /// short encoding would have sufficed.
+    pub fn try_from_canonical(apdu: &'a [u8]) -> Result<Self, Error> {
+        let view = Self::try_from(apdu)?;
+        if view.extended && view.data.len() <= 255 && view.le <= 256 {
+            return Err(Error::NonCanonical);
+        }
+        Ok(view)
+    }
+
+    /// Iterate over the command APDUs of `stream`, which are concatenated without framing
+    ///
+    /// As command APDUs are not self-delimiting (see the [`TryFrom`] implementation),
+    /// the stream is split greedily:
+    ///
+    /// - The first body byte is read as Lc if it is not zero, and the Lc data bytes must follow.
+    ///   Otherwise, if the following two bytes announce data that is present, they are read as
+    ///   extended Lc, else as extended Le. A single `00` at the end of the stream, or followed by
+    ///   fewer than two bytes, is a short Le.
+    /// - After the data, the Le field is read if the stream then ends or if at least a command
+    ///   header remains. A command `00 B0 00 00 02` followed by another command is therefore
+    ///   read as a command with two data bytes.
+    ///
+    /// The iterator fails with [`Error::TooShort`] on trailing bytes that are shorter than a
+    /// header and with [`Error::LcMismatch`] if Lc announces more bytes than are left, and
+    /// returns `None` after an error.
+    pub fn parse_stream(stream: &'a [u8]) -> Stream<'a> {
+        Stream { data: stream }
+    }
+
+    /// Copy the command into an owned [`Command`]
+    ///
+    /// Fails with [`Error::BufferOverflow`] if the data does not fit into `S` bytes.
+    pub fn to_owned<const S: usize>(&self) -> Result<Command<S>, Error> {
         let &CommandView {
             class,
             instruction,
@@ -184,20 +624,248 @@ impl<'a> CommandView<'a> {
             // maximum expected response length
             le,
             // payload
-            data: Data::from_slice(data).map_err(|_| FromSliceError::TooLong)?,
+            data: to_data(data)?,
             extended,
         })
     }
 }
 
+/// Iterator over concatenated command APDUs, see [`CommandView::parse_stream`]
+#[derive(Clone, Debug)]
+pub struct Stream<'a> {
+    data: &'a [u8],
+}
+
+impl Stream<'_> {
+    /// Length of the first APDU of the stream
+    fn next_len(&self) -> Result<usize, Error> {
+        if self.data.len() < 4 {
+            return Err(Error::TooShort);
+        }
+        // the Le field is only read if what follows can be another command
+        let le_len = |rest: usize, len: usize| match rest.checked_sub(len) {
+            Some(0) | Some(4..) => len,
+            _ => 0,
+        };
+        let body_len = match self.data[4..] {
+            [] => 0,
+            [0, high, low, ref rest @ ..] => {
+                let lc = usize::from(u16::from_be_bytes([high, low]));
+                if lc != 0 && rest.len() >= lc {
+                    3 + lc + le_len(rest.len() - lc, 2)
+                } else {
+                    3
+                }
+            }
+            [0, ..] => 1,
+            [lc, ref rest @ ..] => {
+                let lc = usize::from(lc);
+                if rest.len() < lc {
+                    return Err(Error::LcMismatch);
+                }
+                1 + lc + le_len(rest.len() - lc, 1)
+            }
+        };
+        Ok(4 + body_len)
+    }
+}
+
+impl<'a> Iterator for Stream<'a> {
+    type Item = Result<CommandView<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let result = self.next_len().and_then(|len| {
+            let (apdu, rest) = self.data.split_at(len);
+            let view = CommandView::try_from(apdu)?;
+            self.data = rest;
+            Ok(view)
+        });
+        if result.is_err() {
+            self.data = &[];
+        }
+        Some(result)
+    }
+}
+
 impl<const S: usize> TryFrom<&[u8]> for Command<S> {
-    type Error = FromSliceError;
+    type Error = Error;
     fn try_from(apdu: &[u8]) -> core::result::Result<Self, Self::Error> {
         let view: CommandView = apdu.try_into()?;
         view.to_owned()
     }
 }
 
+/// Generates valid commands with at most `S` bytes of data
+///
+/// `extended` is set if required by the data or expected length, and arbitrarily otherwise.
+#[cfg(feature = "arbitrary")]
+impl<'a, const S: usize> arbitrary::Arbitrary<'a> for Command<S> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let class = u.arbitrary()?;
+        let instruction = u.arbitrary()?;
+        let p1 = u.arbitrary()?;
+        let p2 = u.arbitrary()?;
+        let len = u.int_in_range(0..=S.min(65_535))?;
+        let data =
+            Data::from_slice(u.bytes(len)?).map_err(|_| arbitrary::Error::IncorrectFormat)?;
+        let le = u.int_in_range(0..=65_536)?;
+        let extended = data.len() > 255 || le > 256 || bool::arbitrary(u)?;
+        Ok(Self {
+            class,
+            instruction,
+            p1,
+            p2,
+            data,
+            le,
+            extended,
+        })
+    }
+}
+
+/// Wipes the data, including the unused part of the buffer, and the parameters
+#[cfg(feature = "zeroize")]
+impl<const S: usize> zeroize::Zeroize for Command<S> {
+    fn zeroize(&mut self) {
+        crate::zeroize_data(&mut self.data);
+        self.p1.zeroize();
+        self.p2.zeroize();
+        self.le.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<const S: usize> Drop for Command<S> {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<const S: usize> zeroize::ZeroizeOnDrop for Command<S> {}
+
+/// Fields of a serialized [`Command`], before validation
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(rename = "Command")]
+struct SerializedCommand<const S: usize> {
+    class: class::Class,
+    instruction: Instruction,
+    p1: u8,
+    p2: u8,
+    data: Data<S>,
+    le: usize,
+    extended: bool,
+}
+
+/// Validated like [`Command::try_new`]: `le` must not exceed 65536, and `extended` may only be
+/// unset if the command fits into a short APDU.
+#[cfg(feature = "serde")]
+impl<'de, const S: usize> serde::Deserialize<'de> for Command<S> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fields = SerializedCommand::<S>::deserialize(deserializer)?;
+        if fields.le > 65_536 {
+            return Err(serde::de::Error::custom(Error::InvalidSliceLength));
+        }
+        let mut command = Self::try_new(
+            fields.class,
+            fields.instruction,
+            fields.p1,
+            fields.p2,
+            &fields.data,
+            Le::from_expected(fields.le),
+        )
+        .map_err(serde::de::Error::custom)?;
+        if command.extended && !fields.extended {
+            return Err(serde::de::Error::custom(Error::ExtendedRequired));
+        }
+        command.extended = fields.extended;
+        Ok(command)
+    }
+}
+
+// Equality between the different command representations compares the logical command (class,
+// instruction, parameters, data and expected length), so that short and extended encodings
+// of the same command are equal. The command chaining bit of the class is ignored, so that a
+// reassembled chain equals the original command: all other bits of CLA (secure messaging and
+// logical channel) participate. Use `bitwise_eq` to also compare the chaining bit.
+macro_rules! impl_logical_eq {
+    ($([$($generics:tt)*] $lhs:ty, $rhs:ty;)*) => {
+        $(
+            impl_logical_eq!(@impl [$($generics)*] $lhs, $rhs);
+            impl_logical_eq!(@impl [$($generics)*] $rhs, $lhs);
+        )*
+    };
+    (@same $([$($generics:tt)*] $lhs:ty, $rhs:ty;)*) => {
+        $(
+            impl_logical_eq!(@impl [$($generics)*] $lhs, $rhs);
+        )*
+    };
+    (@impl [$($generics:tt)*] $lhs:ty, $rhs:ty) => {
+        impl<$($generics)*> PartialEq<$rhs> for $lhs {
+            fn eq(&self, other: &$rhs) -> bool {
+                self.class().without_chaining() == other.class().without_chaining()
+                    && self.instruction() == other.instruction()
+                    && self.p1 == other.p1
+                    && self.p2 == other.p2
+                    && self.data()[..] == other.data()[..]
+                    && self.expected() == other.expected()
+            }
+        }
+    };
+}
+
+impl_logical_eq! {
+    ['a, const S: usize] Command<S>, CommandView<'a>;
+    ['a, D: AsRef<[u8]>] CommandBuilder<D>, CommandView<'a>;
+    [D: AsRef<[u8]>, const S: usize] CommandBuilder<D>, Command<S>;
+}
+
+impl_logical_eq! {
+    @same
+    [const S: usize, const T: usize] Command<S>, Command<T>;
+    ['a, 'b] CommandView<'a>, CommandView<'b>;
+    [D: AsRef<[u8]>, E: AsRef<[u8]>] CommandBuilder<D>, CommandBuilder<E>;
+}
+
+impl<D: AsRef<[u8]>> Eq for CommandBuilder<D> {}
+
+/// Compact `CLA INS P1 P2 Lc Le` summary, without the data
+#[cfg(feature = "defmt")]
+impl<const S: usize> defmt::Format for Command<S> {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(
+            f,
+            "{=u8:02X} {=u8:02X} {=u8:02X} {=u8:02X} Lc={=usize} Le={=usize}",
+            self.class.into_inner(),
+            u8::from(self.instruction),
+            self.p1,
+            self.p2,
+            self.data.len(),
+            self.le,
+        )
+    }
+}
+
+/// Compact `CLA INS P1 P2 Lc Le` summary, without the data
+#[cfg(feature = "defmt")]
+impl defmt::Format for CommandView<'_> {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(
+            f,
+            "{=u8:02X} {=u8:02X} {=u8:02X} {=u8:02X} Lc={=usize} Le={=usize}",
+            self.class.into_inner(),
+            u8::from(self.instruction),
+            self.p1,
+            self.p2,
+            self.data.len(),
+            self.le,
+        )
+    }
+}
+
 // cf. ISO 7816-3, 12.1.3: Decoding conventions for command APDUs
 // freely available version:
 // http://www.ttfn.net/techno/smartcards/iso7816_4.html#table5
@@ -210,6 +878,13 @@ struct ParsedLengths {
     extended: bool,
 }
 
+fn to_data<const S: usize>(data: &[u8]) -> Result<Data<S>, Error> {
+    Data::from_slice(data).map_err(|_| Error::BufferOverflow {
+        needed: data.len(),
+        capacity: S,
+    })
+}
+
 #[inline(always)]
 fn replace_zero(value: usize, replacement: usize) -> usize {
     if value == 0 {
@@ -219,7 +894,7 @@ fn replace_zero(value: usize, replacement: usize) -> usize {
     }
 }
 #[inline]
-fn parse_lengths(body: &[u8]) -> Result<ParsedLengths, FromSliceError> {
+fn parse_lengths(body: &[u8]) -> Result<ParsedLengths, Error> {
     // Encoding rules:
     // - Lc or Le = 0 => leave out
     // - short + extended length fields shall not be combined
@@ -270,9 +945,12 @@ fn parse_lengths(body: &[u8]) -> Result<ParsedLengths, FromSliceError> {
 
     // only extended cases left now
     if b1 != 0 {
-        return Err(FromSliceError::InvalidFirstBodyByteForExtended);
+        if l < 1 + b1 {
+            return Err(Error::LcMismatch);
+        }
+        return Err(Error::InvalidFirstBodyByteForExtended);
     } else if l < 3 {
-        return Err(FromSliceError::InvalidSliceLength);
+        return Err(Error::InvalidSliceLength);
     }
 
     // Case 2E (no data)
@@ -301,11 +979,16 @@ fn parse_lengths(body: &[u8]) -> Result<ParsedLengths, FromSliceError> {
         return Ok(parsed);
     }
 
-    // If we haven’t returned yet, the slice has an invalid length:  Either the encoded lc value is
-    // wrong, or the lc and le lengths are not encoded properly (one byte per value for simple
-    // APDU, two bytes per value for extended APDU).
+    if l < 3 + parsed.lc {
+        return Err(Error::LcMismatch);
+    } else if l > 5 + parsed.lc {
+        return Err(Error::TrailingBytes);
+    }
+
+    // If we haven’t returned yet, the slice has an invalid length: the Le field is a single byte,
+    // which is not allowed for extended APDUs.
 
-    Err(FromSliceError::InvalidSliceLength)
+    Err(Error::InvalidSliceLength)
 }
 
 #[cfg(test)]
@@ -322,6 +1005,574 @@ mod test {
         assert_eq!(lengths.offset, 1);
     }
 
+    #[test]
+    fn chaining() {
+        let first = CommandView::try_from(&hex!("10DB3FFF 02 0102")[..]).unwrap();
+        let last = CommandView::try_from(&hex!("00DB3FFF 01 03")[..]).unwrap();
+        assert!(first.is_chained());
+        assert!(!last.is_chained());
+
+        let mut command: Command<8> = first.to_owned().unwrap();
+        assert!(command.is_chained());
+        command.extend_from_command_view(&last).unwrap();
+        assert!(!command.is_chained());
+        assert!(!command.as_view().is_chained());
+        assert_eq!(command.data(), &hex!("010203"));
+
+        let mut command: Command<8> = first.to_owned().unwrap();
+        command.clear_chaining();
+        assert!(!command.is_chained());
+        assert_eq!(command.class().into_inner(), 0x00);
+    }
+
+    #[test]
+    fn logical_eq() {
+        let data = hex!("0102");
+        let builder = CommandBuilder::new(
+            class::Class::try_from(0x00).unwrap(),
+            Instruction::Select,
+            0x04,
+            0x00,
+            &data[..],
+            256,
+        );
+        let short = hex!("00A40400 02 0102 00");
+        let extended = hex!("00A40400 000002 0102 0100");
+        for apdu in [&short[..], &extended] {
+            let view = CommandView::try_from(apdu).unwrap();
+            let command = Command::<16>::try_from(apdu).unwrap();
+            assert_eq!(builder, view);
+            assert_eq!(view, builder);
+            assert_eq!(builder, command);
+            assert_eq!(command, builder);
+            assert_eq!(view, command);
+            assert_eq!(command, view);
+        }
+        let short_view = CommandView::try_from(&short[..]).unwrap();
+        let extended_view = CommandView::try_from(&extended[..]).unwrap();
+        assert_eq!(short_view, extended_view);
+        assert_eq!(
+            Command::<16>::try_from(&short[..]).unwrap(),
+            Command::<8>::try_from(&extended[..]).unwrap()
+        );
+        assert!(!Command::<16>::try_from(&short[..])
+            .unwrap()
+            .bitwise_eq(&extended_view));
+        let mut other = builder;
+        other.p2 = 0x0C;
+        let view = CommandView::try_from(&short[..]).unwrap();
+        assert_ne!(other, view);
+        assert_ne!(view, other);
+
+        // only the chaining bit is ignored
+        let chained = CommandView::try_from(&hex!("10A40400 02 0102 00")[..]).unwrap();
+        let command = Command::<16>::try_from(&short[..]).unwrap();
+        assert_eq!(builder, chained);
+        assert_eq!(command, chained);
+        assert_eq!(view, chained);
+        assert_eq!(builder, CommandBuilder::from(chained.clone()));
+        assert_eq!(
+            command,
+            Command::<16>::try_from(&hex!("10A40400 02 0102 00")[..]).unwrap()
+        );
+        assert!(!builder.bitwise_eq(&chained));
+        assert!(!command.bitwise_eq(&chained));
+        assert!(builder.bitwise_eq(&view));
+        assert!(command.bitwise_eq(&view));
+        let channel = CommandView::try_from(&hex!("01A40400 02 0102 00")[..]).unwrap();
+        assert_ne!(builder, channel);
+        assert_ne!(command, channel);
+    }
+
+    #[test]
+    fn extended_fields() {
+        for (apdu, lc_extended, le_extended) in [
+            (&hex!("00A40400")[..], false, false),
+            (&hex!("00A40400 00"), false, false),
+            (&hex!("00A40400 02 0102 00"), false, false),
+            (&hex!("00A40400 000000"), false, true),
+            (&hex!("00A40400 000002 0102"), true, false),
+            (&hex!("00A40400 000002 0102 0000"), true, true),
+        ] {
+            let view = CommandView::try_from(apdu).unwrap();
+            assert_eq!(view.lc_extended(), lc_extended);
+            assert_eq!(view.le_extended(), le_extended);
+            let command = Command::<16>::try_from(apdu).unwrap();
+            assert_eq!(command.lc_extended(), lc_extended);
+            assert_eq!(command.le_extended(), le_extended);
+        }
+    }
+
+    #[test]
+    fn write_hex() {
+        for (apdu, hex) in [
+            (&hex!("00A40400")[..], &b"00 A4 04 00"[..]),
+            (&hex!("00A40400 02 A000 00"), b"00 A4 04 00 02 A0 00 00"),
+            // the extended encoding is kept
+            (&hex!("00B00000 000100"), b"00 B0 00 00 00 01 00"),
+        ] {
+            let mut buffer = heapless::Vec::<u8, 32>::new();
+            let view = CommandView::try_from(apdu).unwrap();
+            view.write_hex(&mut buffer).unwrap();
+            assert_eq!(buffer, hex);
+        }
+
+        let mut buffer = heapless::Vec::<u8, 10>::new();
+        let view = CommandView::try_from(&hex!("00A40400")[..]).unwrap();
+        assert_eq!(view.write_hex(&mut buffer), Err(BufferFull));
+    }
+
+    #[test]
+    fn serialize_into() {
+        for apdu in [
+            &hex!("00A40400")[..],
+            &hex!("00A40400 02 A000 00"),
+            &hex!("00B00000 000400"),
+            &hex!("00DA0000 000002 0102"),
+        ] {
+            let command = Command::<4>::try_from(apdu).unwrap();
+            let builder = CommandBuilder::from(command.as_view());
+            for supports_extended in [false, true] {
+                let mut buffer = heapless::Vec::<u8, 16>::new();
+                let mut expected = heapless::Vec::<u8, 16>::new();
+                assert_eq!(
+                    command.serialize_into(&mut buffer, supports_extended),
+                    builder.serialize_into(&mut expected, supports_extended)
+                );
+                assert_eq!(buffer, expected);
+                assert_eq!(
+                    command.serialize_to_heapless::<16>(supports_extended),
+                    Ok(expected)
+                );
+            }
+        }
+
+        let command = Command::<4>::try_from(&hex!("00B00000 000400")[..]).unwrap();
+        assert_eq!(
+            command.serialize_to_heapless::<16>(true).unwrap(),
+            hex!("00B00000 000400")
+        );
+        assert_eq!(
+            command.serialize_to_heapless::<16>(false).unwrap(),
+            hex!("00B00000 00")
+        );
+        assert_eq!(command.serialize_to_heapless::<6>(true), Err(BufferFull));
+    }
+
+    #[test]
+    fn p12() {
+        let apdu = hex!("00CA 5FC1 00");
+        assert_eq!(CommandView::try_from(&apdu[..]).unwrap().p12(), 0x5FC1);
+        assert_eq!(Command::<0>::try_from(&apdu[..]).unwrap().p12(), 0x5FC1);
+    }
+
+    #[test]
+    fn short_le_zero() {
+        let case1 = CommandView::try_from(&hex!("00A40400")[..]).unwrap();
+        assert_eq!(case1.case(), Case::Case1);
+        assert_eq!(case1.le(), Le::None);
+        assert_eq!(case1.expected(), 0);
+
+        let case2 = CommandView::try_from(&hex!("00A40400 00")[..]).unwrap();
+        assert_eq!(case2.case(), Case::Case2);
+        assert_eq!(case2.le(), Le::Exact(256));
+        assert_eq!(case2.expected(), 256);
+        assert!(case2.data().is_empty());
+
+        let command = Command::<16>::try_from(&hex!("00A40400 00")[..]).unwrap();
+        assert_eq!(command.case(), Case::Case2);
+        assert_eq!(command.le(), Le::Exact(256));
+        assert_eq!(command.expected(), 256);
+        assert_eq!(command, case2);
+    }
+
+    #[test]
+    fn canonical() {
+        for apdu in [
+            &hex!("00A40400")[..],
+            &hex!("00A40400 00"),
+            &hex!("00A40400 02 0102 00"),
+            &hex!("00A40400 000101"),
+            &hex!("00A40400 000000"),
+            &hex!("00A40400 000002 0102 0000"),
+        ] {
+            assert_eq!(
+                CommandView::try_from_canonical(apdu),
+                CommandView::try_from(apdu)
+            );
+        }
+        let mut long = [0; 4 + 3 + 256];
+        long[5..7].copy_from_slice(&[0x01, 0x00]);
+        assert!(CommandView::try_from_canonical(&long).is_ok());
+
+        for apdu in [
+            &hex!("00A40400 000100")[..],
+            &hex!("00A40400 000010"),
+            &hex!("00A40400 000002 0102"),
+            &hex!("00A40400 000002 0102 0100"),
+        ] {
+            assert!(CommandView::try_from(apdu).is_ok());
+            assert_eq!(
+                CommandView::try_from_canonical(apdu),
+                Err(Error::NonCanonical)
+            );
+        }
+        assert_eq!(
+            CommandView::try_from_canonical(&hex!("00A404")),
+            Err(Error::TooShort)
+        );
+    }
+
+    #[test]
+    fn try_into_capacity() {
+        let apdu = hex!("00A40400 03 010203 00");
+        let command = Command::<16>::try_from(&apdu[..]).unwrap();
+        let smaller: Command<3> = command.clone().try_into_capacity().unwrap();
+        assert_eq!(smaller.data(), &hex!("010203"));
+        assert_eq!(smaller.expected(), 256);
+        assert_eq!(smaller, Command::<3>::try_from(&apdu[..]).unwrap());
+
+        let larger = smaller.try_into_capacity::<32>().unwrap();
+        assert_eq!(larger, command.as_view());
+
+        assert_eq!(command.clone().try_into_capacity::<2>(), Err(command));
+    }
+
+    #[test]
+    fn error_display() {
+        assert_eq!(
+            Error::BufferOverflow {
+                needed: 5,
+                capacity: 4
+            }
+            .to_string(),
+            "command data of 5 bytes exceeds the capacity of 4 bytes"
+        );
+        assert_eq!(Error::LcMismatch.to_string(), "Lc larger than the data");
+    }
+
+    #[test]
+    fn parse_stream() {
+        let stream = hex!(
+            "00A40400 02 A000 00"
+            "00B00000 000100"
+            "80CA0000 000002 0102 0000"
+            "00DA0000 02 0102"
+        );
+        let views: heapless::Vec<_, 8> = CommandView::parse_stream(&stream).collect();
+        let expected = [
+            &hex!("00A40400 02 A000 00")[..],
+            &hex!("00B00000 000100"),
+            &hex!("80CA0000 000002 0102 0000"),
+            &hex!("00DA0000 02 0102"),
+        ]
+        .map(|apdu| Ok(CommandView::try_from(apdu).unwrap()));
+        assert_eq!(views, expected);
+
+        // trailing bytes that cannot form a header are not taken as Le
+        let stream = hex!("00B00000 02 0102 0304");
+        let mut views = CommandView::parse_stream(&stream);
+        assert_eq!(views.next().unwrap().unwrap().case(), Case::Case3);
+        assert_eq!(views.next(), Some(Err(Error::TooShort)));
+        assert_eq!(views.next(), None);
+
+        // a short Le followed by another command is read as Lc
+        let stream = hex!("00C00000 10 00DA0000 02 0102");
+        let mut views = CommandView::parse_stream(&stream);
+        assert_eq!(views.next(), Some(Err(Error::LcMismatch)));
+        assert_eq!(views.next(), None);
+
+        for (stream, error) in [
+            (&hex!("00A4")[..], Error::TooShort),
+            (&hex!("00A40400 05 0102"), Error::LcMismatch),
+            (&hex!("FFA40400"), Error::InvalidClass),
+        ] {
+            let mut views = CommandView::parse_stream(stream);
+            assert_eq!(views.next(), Some(Err(error)), "{stream:02X?}");
+            assert_eq!(views.next(), None);
+        }
+        assert_eq!(CommandView::parse_stream(&[]).next(), None);
+    }
+
+    #[test]
+    fn not_self_delimiting() {
+        let stream = hex!("00B00000 02 0102");
+        let whole = CommandView::try_from(&stream[..]).unwrap();
+        assert_eq!(whole.case(), Case::Case3);
+        let prefix = CommandView::try_from(&stream[..5]).unwrap();
+        assert_eq!(prefix.case(), Case::Case2);
+    }
+
+    #[test]
+    fn parse_errors() {
+        for (apdu, error) in [
+            (&hex!("00A404")[..], Error::TooShort),
+            (&hex!("FFA40400"), Error::InvalidClass),
+            (&hex!("00A40400 05 010203"), Error::LcMismatch),
+            (
+                &hex!("00A40400 02 0102 0000"),
+                Error::InvalidFirstBodyByteForExtended,
+            ),
+            (&hex!("00A40400 0001"), Error::InvalidSliceLength),
+            (&hex!("00A40400 000005 010203"), Error::LcMismatch),
+            (&hex!("00A40400 000002 0102 00"), Error::InvalidSliceLength),
+            (&hex!("00A40400 000002 0102 0000 00"), Error::TrailingBytes),
+        ] {
+            assert_eq!(CommandView::try_from(apdu), Err(error), "{}", hexstr!(apdu));
+        }
+    }
+
+    #[test]
+    fn case() {
+        for (apdu, case) in [
+            (&hex!("00A40400")[..], Case::Case1),
+            (&hex!("00A40400 00"), Case::Case2),
+            (&hex!("00A40400 000000"), Case::Case2),
+            (&hex!("00A40400 02 0102"), Case::Case3),
+            (&hex!("00A40400 02 0102 00"), Case::Case4),
+            (&hex!("00A40400 000002 0102 0000"), Case::Case4),
+        ] {
+            assert_eq!(CommandView::try_from(apdu).unwrap().case(), case);
+            assert_eq!(Command::<16>::try_from(apdu).unwrap().case(), case);
+        }
+    }
+
+    #[test]
+    fn header() {
+        let view = CommandView::try_from(&hex!("00A40401 02 0102")[..]).unwrap();
+        assert_eq!(view.class().into_inner(), 0x00);
+        assert_eq!(view.instruction(), Instruction::Select);
+        assert_eq!(view.p1, 0x04);
+        assert_eq!(view.p2, 0x01);
+    }
+
+    #[test]
+    fn to_owned() {
+        let apdu = &hex!("00A40400 03 010203 00");
+        let owned: Command<3> = CommandView::try_from(&apdu[..])
+            .unwrap()
+            .to_owned()
+            .unwrap();
+        assert_eq!(owned.data(), &hex!("010203"));
+        assert_eq!(owned.expected(), 256);
+        assert_eq!(
+            CommandView::try_from(&apdu[..]).unwrap().to_owned::<2>(),
+            Err(Error::BufferOverflow {
+                needed: 3,
+                capacity: 2
+            })
+        );
+    }
+
+    #[test]
+    fn capacity() {
+        assert_eq!(Command::<16>::CAPACITY, 16);
+        assert!(Command::<16>::fits(16));
+        assert!(!Command::<16>::fits(17));
+        crate::assert_command_capacity!(16, 16);
+    }
+
+    #[test]
+    fn new() {
+        let class = class::Class::try_from(0x00).unwrap();
+        let command = Command::<4>::new(class, Instruction::Verify, 0x00, 0x81, b"1234", 0);
+        assert_eq!(
+            command,
+            Command::<4>::try_from(&hex!("00200081 04 31323334")[..]).unwrap()
+        );
+        assert!(!command.extended);
+        assert_eq!(
+            Command::<4>::try_new(class, Instruction::Verify, 0x00, 0x81, b"12345", 0),
+            Err(Error::BufferOverflow {
+                needed: 5,
+                capacity: 4
+            })
+        );
+        let command =
+            Command::<4>::try_new(class, Instruction::GetResponse, 0x00, 0x00, &[], Le::Max)
+                .unwrap();
+        assert!(command.extended);
+        assert_eq!(command.le(), Le::Max);
+    }
+
+    #[test]
+    fn data_mut() {
+        let mut command = Command::<4>::try_from(&hex!("00200081 02 3132")[..]).unwrap();
+        command.data_mut()[0] = 0x30;
+        assert_eq!(command.data(), &hex!("3032"));
+        command.set_data(&hex!("31323334")).unwrap();
+        assert_eq!(command.data(), &hex!("31323334"));
+        assert_eq!(
+            command.set_data(&hex!("3132333435")),
+            Err(Error::BufferOverflow {
+                needed: 5,
+                capacity: 4
+            })
+        );
+        assert_eq!(command.data(), &hex!("31323334"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        use serde_test::{assert_de_tokens, assert_de_tokens_error, assert_tokens, Token};
+
+        let command = Command::<4>::try_from(&hex!("10A40400 02 0102 00")[..]).unwrap();
+        let tokens = [
+            Token::Struct {
+                name: "Command",
+                len: 7,
+            },
+            Token::Str("class"),
+            Token::U8(0x10),
+            Token::Str("instruction"),
+            Token::U8(0xA4),
+            Token::Str("p1"),
+            Token::U8(0x04),
+            Token::Str("p2"),
+            Token::U8(0x00),
+            Token::Str("data"),
+            Token::Seq { len: Some(2) },
+            Token::U8(0x01),
+            Token::U8(0x02),
+            Token::SeqEnd,
+            Token::Str("le"),
+            Token::U64(256),
+            Token::Str("extended"),
+            Token::Bool(false),
+            Token::StructEnd,
+        ];
+        assert_tokens(&command, &tokens);
+
+        let invalid = [tokens[0], tokens[1], Token::U8(0xFF)];
+        assert_de_tokens_error::<Command<4>>(
+            &invalid,
+            "invalid value: integer `255`, expected a class byte other than 0xFF",
+        );
+
+        let mut invalid = tokens;
+        invalid[15] = Token::U64(65_537);
+        assert_de_tokens_error::<Command<4>>(&invalid, "invalid length fields");
+        invalid[15] = Token::U64(257);
+        assert_de_tokens_error::<Command<4>>(&invalid, "extended length fields required");
+        // valid with the extended encoding
+        invalid[17] = Token::Bool(true);
+        let extended = Command::<4>::try_from(&hex!("10A40400 000002 0102 0101")[..]).unwrap();
+        assert_de_tokens(&extended, &invalid);
+        assert_tokens(&Instruction::Unknown(0x12), &[Token::U8(0x12)]);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let bytes: Vec<u8> = (0..=255).cycle().step_by(7).take(4096).collect();
+        let mut u = Unstructured::new(&bytes);
+        let mut count = 0;
+        while let Ok(command) = Command::<300>::arbitrary(&mut u) {
+            let mut buffer = heapless::Vec::<u8, 1024>::new();
+            let builder = CommandBuilder::new(
+                command.class(),
+                command.instruction(),
+                command.p1,
+                command.p2,
+                command.data(),
+                Le::from_expected(command.expected()),
+            );
+            builder
+                .serialize_into(&mut buffer, command.extended)
+                .unwrap()
+                .unwrap();
+            let view = CommandView::try_from(buffer.as_slice()).unwrap();
+            assert_eq!(view.class(), command.class());
+            assert_eq!(view.instruction(), command.instruction());
+            assert_eq!(view.data(), command.data().as_slice());
+            assert_eq!(view.expected(), command.expected());
+            count += 1;
+        }
+        assert!(count > 1);
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn zeroize() {
+        use zeroize::Zeroize;
+
+        let mut command = Command::<8>::try_from(&hex!("00200081 06 313233343536")[..]).unwrap();
+        command.set_data(&hex!("3132")).unwrap();
+        command.zeroize();
+        assert!(command.data().is_empty());
+        assert_eq!(command.p2, 0);
+        // the bytes beyond the logical length are wiped as well
+        let data = command.data_mut();
+        data.resize_default(8).unwrap();
+        assert_eq!(data, &[0; 8]);
+    }
+
+    #[test]
+    fn expected() {
+        for (apdu, expected) in [
+            (&hex!("00B00000")[..], 0),
+            (&hex!("00B00000 00"), 256),
+            (&hex!("00B00000 10"), 16),
+            (&hex!("00B00000 000000"), 65_536),
+            (&hex!("00B00000 000100"), 256),
+            (&hex!("00D60000 01 01 00"), 256),
+            (&hex!("00D60000 000001 01 0000"), 65_536),
+        ] {
+            assert_eq!(CommandView::try_from(apdu).unwrap().expected(), expected);
+        }
+    }
+
+    #[test]
+    fn lc_le() {
+        for (apdu, lc, le) in [
+            (&hex!("00A40400")[..], 0, Le::None),
+            (&hex!("00A40400 00"), 0, Le::Exact(256)),
+            (&hex!("00A40400 10"), 0, Le::Exact(16)),
+            (&hex!("00A40400 000000"), 0, Le::Max),
+            (&hex!("00A40400 000100"), 0, Le::Exact(256)),
+            (&hex!("00A40400 02 0102"), 2, Le::None),
+            (&hex!("00A40400 02 0102 00"), 2, Le::Exact(256)),
+            (&hex!("00A40400 000002 0102 0000"), 2, Le::Max),
+            (&hex!("00A40400 000002 0102 0102"), 2, Le::Exact(0x0102)),
+        ] {
+            let view = CommandView::try_from(apdu).unwrap();
+            assert_eq!(view.lc(), lc);
+            assert_eq!(view.le(), le);
+            let command = Command::<16>::try_from(apdu).unwrap();
+            assert_eq!(command.lc(), lc);
+            assert_eq!(command.le(), le);
+
+            let rebuilt = Command::<16>::new(
+                view.class(),
+                view.instruction(),
+                view.p1,
+                view.p2,
+                view.data(),
+                view.le(),
+            );
+            assert_eq!(rebuilt, view, "{apdu:02X?}");
+            let rebuilt = CommandBuilder::new(
+                view.class(),
+                view.instruction(),
+                view.p1,
+                view.p2,
+                view.data(),
+                view.le(),
+            );
+            assert_eq!(rebuilt, view, "{apdu:02X?}");
+        }
+
+        let view = CommandView::try_from(&hex!("00A40400 000100")[..]).unwrap();
+        assert_eq!(view.le(), Le::from(256));
+        assert_eq!(CommandBuilder::from(view).expected(), 256);
+        assert_eq!(Le::Exact(0).expected(), Le::None.expected());
+        assert_eq!(Le::from_expected(65_536), Le::Max);
+        assert_eq!(Le::from_expected(256), Le::Exact(256));
+    }
+
     #[test]
     fn command_chaining() {
         let apdu = &[
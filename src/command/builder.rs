@@ -0,0 +1,1484 @@
+use super::{
+    class::Class,
+    security::{MseOperation, PsoOperation},
+    Case, Error, Instruction, Le,
+};
+#[cfg(feature = "async")]
+use crate::writer::AsyncWriter;
+use crate::writer::{BufferFull, Writer};
+use crate::{Aid, Data};
+
+const HEADER_LEN: usize = 4;
+const MAX_SHORT_LC: usize = 255;
+const MAX_SHORT_LE: usize = 256;
+const MAX_EXTENDED_LC: usize = 65_535;
+const MAX_EXTENDED_LE: usize = 65_536;
+
+/// Highest offset that can be encoded in P1-P2, the most significant bit indicating a short EF
+/// identifier
+const MAX_OFFSET: u16 = 0x7FFF;
+
+/// Builder for command APDUs
+///
+/// Unlike [`Command`](super::Command), the data is borrowed (or owned by `D`) instead of being
+/// copied into a fixed-size buffer. The builder can be serialized into any [`Writer`], using
+/// command chaining if the data does not fit into a single APDU.
+#[derive(Clone, Copy, Debug)]
+pub struct CommandBuilder<D> {
+    class: Class,
+    instruction: Instruction,
+
+    pub p1: u8,
+    pub p2: u8,
+
+    data: D,
+
+    le: usize,
+}
+
+impl<D: AsRef<[u8]>> CommandBuilder<D> {
+    /// Create a command. `le` is the maximum number of expected response bytes, see [`Le`]
+    ///
+    /// A `u16` converts into [`Le::Exact`]. `Le::Exact(256)` is serialized as `00` in short
+    /// APDUs, and [`Le::Max`] as `0000` in extended APDUs (`000000` without data), or as `00`
+    /// if the card only supports short APDUs.
+    pub fn new(
+        class: Class,
+        instruction: Instruction,
+        p1: u8,
+        p2: u8,
+        data: D,
+        le: impl Into<Le>,
+    ) -> Self {
+        Self {
+            class,
+            instruction,
+            p1,
+            p2,
+            data,
+            le: le.into().expected(),
+        }
+    }
+
+    /// Create a command with P1-P2 given as one big endian value, e.g. an offset or a tag
+    pub fn new_p12(
+        class: Class,
+        instruction: Instruction,
+        p12: u16,
+        data: D,
+        le: impl Into<Le>,
+    ) -> Self {
+        let [p1, p2] = p12.to_be_bytes();
+        Self::new(class, instruction, p1, p2, data, le)
+    }
+
+    /// Create a case 3 command: data and no Le field, serialized as `CLA INS P1 P2 Lc data`
+    ///
+    /// This is equivalent to [`new`](Self::new) with [`Le::None`]: the Le field is absent, not
+    /// `00` (which would request 256 bytes).
+    pub fn case3(class: Class, instruction: Instruction, p1: u8, p2: u8, data: D) -> Self {
+        Self::new(class, instruction, p1, p2, data, Le::None)
+    }
+
+    pub fn class(&self) -> Class {
+        self.class
+    }
+
+    /// Replace the class, e.g. to change the logical channel or the secure messaging indication
+    pub const fn with_class(mut self, class: Class) -> Self {
+        self.class = class;
+        self
+    }
+
+    pub const fn with_p1(mut self, p1: u8) -> Self {
+        self.p1 = p1;
+        self
+    }
+
+    pub const fn with_p2(mut self, p2: u8) -> Self {
+        self.p2 = p2;
+        self
+    }
+
+    pub fn instruction(&self) -> Instruction {
+        self.instruction
+    }
+
+    /// P1 and P2 as one big endian value
+    pub fn p12(&self) -> u16 {
+        u16::from_be_bytes([self.p1, self.p2])
+    }
+
+    pub fn data(&self) -> &[u8] {
+        self.data.as_ref()
+    }
+
+    /// Maximum number of expected response bytes (Ne), `0` if no response data is expected
+    pub fn expected(&self) -> usize {
+        self.le
+    }
+
+    pub fn case(&self) -> Case {
+        Case::new(self.data().len(), self.le)
+    }
+
+    /// Compare with `other` like `==`, but including the command chaining bit of the class
+    ///
+    /// Unlike [`Command::bitwise_eq`](super::Command::bitwise_eq), the length encoding is not
+    /// compared, as the builder only chooses it when serializing.
+    pub fn bitwise_eq(&self, other: &super::CommandView<'_>) -> bool {
+        self.class == other.class() && self == other
+    }
+
+    /// Number of bytes that [`serialize_into`](Self::serialize_into) writes, given a writer with
+    /// enough space left
+    ///
+    /// If the data is too long for a single APDU (more than 255 bytes without
+    /// `supports_extended`, more than 65535 bytes with it), this is the length of the first
+    /// APDU of the chain. Lc is never truncated: longer data is always sent using command
+    /// chaining.
+    pub fn serialized_len(&self, supports_extended: bool) -> usize {
+        self.as_borrowed()
+            .next_apdu(usize::MAX, supports_extended)
+            .map(|apdu| apdu.len())
+            .unwrap_or_default()
+    }
+
+    /// Returns `true` if the command does not fit into a single APDU of at most `max_apdu_len`
+    /// bytes, and therefore needs command chaining
+    pub fn requires_chaining(&self, max_apdu_len: usize, supports_extended: bool) -> bool {
+        match self
+            .as_borrowed()
+            .next_apdu(max_apdu_len, supports_extended)
+        {
+            Ok(apdu) => apdu.remainder.is_some(),
+            Err(BufferFull) => true,
+        }
+    }
+
+    /// Check that the command can be sent without truncating Le or chaining because of Lc
+    ///
+    /// Without `supports_extended`, [`serialize_into`](Self::serialize_into) truncates Le to
+    /// 256 and sends data longer than 255 bytes using command chaining. Callers that would
+    /// rather fail call this first: it fails with [`Error::ExtendedRequired`] if the data is
+    /// longer than 255 bytes or Le is greater than 256 and `supports_extended` is `false`.
+    pub fn check_length(&self, supports_extended: bool) -> Result<(), Error> {
+        if !supports_extended && (self.data().len() > MAX_SHORT_LC || self.le > MAX_SHORT_LE) {
+            return Err(Error::ExtendedRequired);
+        }
+        Ok(())
+    }
+
+    /// Serialize the command into `writer`
+    ///
+    /// If the command does not fit into a single APDU, either because of the space left in
+    /// `writer` or because the data is too long for the available length encoding, the first
+    /// part is written with the chaining bit set in CLA and the remaining command is returned
+    /// as `Ok(Err(rem))`. `rem` must then be serialized into the next buffer. Otherwise, the
+    /// number of bytes written is returned as `Ok(Ok(len))`.
+    ///
+    /// Short encoding is always preferred: with `supports_extended`, extended encoding is only
+    /// used if the data is longer than 255 bytes or Le is greater than 256. Without
+    /// `supports_extended`, Le is truncated to 256: use [`check_length`](Self::check_length) to
+    /// reject such commands instead.
+    ///
+    /// Fails with [`BufferFull`] if `writer` cannot hold an APDU with at least one data byte,
+    /// or, for a command without data, the complete APDU including Le: chaining only splits the
+    /// data.
+    ///
+    /// The class `0xEF` cannot be chained, as setting the chaining bit would yield the invalid
+    /// class byte `0xFF`: such a command fails with [`BufferFull`] if it requires chaining.
+    pub fn serialize_into<W: Writer>(
+        &self,
+        writer: &mut W,
+        supports_extended: bool,
+    ) -> Result<Result<usize, CommandBuilder<&[u8]>>, W::Error> {
+        self.as_borrowed()
+            .serialize_borrowed(writer, supports_extended)
+    }
+
+    /// Serialize the command as a single short APDU, whatever the card supports
+    ///
+    /// Fails with [`Error::ExtendedRequired`] if the data is longer than 255 bytes or Le is
+    /// greater than 256, and with [`Error::BufferOverflow`] if the APDU does not fit into `N`
+    /// bytes. Unlike [`serialize_into`](Self::serialize_into), Le is never truncated and the
+    /// command is never chained.
+    pub fn encode_short<const N: usize>(&self) -> Result<heapless::Vec<u8, N>, Error> {
+        self.check_length(false)?;
+        self.encode(false)
+    }
+
+    /// Serialize the command as a single extended APDU, even if the short encoding would
+    /// suffice
+    ///
+    /// A command without data and without Le has no length fields and is serialized as its
+    /// header. Fails with [`Error::InvalidSliceLength`] if the data is longer than 65535 bytes,
+    /// and with [`Error::BufferOverflow`] if the APDU does not fit into `N` bytes.
+    pub fn encode_extended<const N: usize>(&self) -> Result<heapless::Vec<u8, N>, Error> {
+        if self.data().len() > MAX_EXTENDED_LC {
+            return Err(Error::InvalidSliceLength);
+        }
+        self.encode(true)
+    }
+
+    fn encode<const N: usize>(&self, extended: bool) -> Result<heapless::Vec<u8, N>, Error> {
+        let apdu = Apdu::new(
+            self.header(self.class),
+            self.data(),
+            self.le,
+            extended,
+            None,
+        );
+        let mut buffer = heapless::Vec::new();
+        for part in apdu.parts() {
+            buffer
+                .extend_from_slice(part)
+                .map_err(|_| Error::BufferOverflow {
+                    needed: apdu.len(),
+                    capacity: N,
+                })?;
+        }
+        Ok(buffer)
+    }
+
+    /// Write the command as a single APDU with the given length encoding
+    pub(super) fn write_apdu<W: Writer + ?Sized>(
+        &self,
+        extended: bool,
+        writer: &mut W,
+    ) -> Result<(), W::Error> {
+        let apdu = Apdu::new(
+            self.header(self.class),
+            self.data(),
+            self.le,
+            extended,
+            None,
+        );
+        for part in apdu.parts() {
+            writer.write_all(part)?;
+        }
+        Ok(())
+    }
+
+    /// Serialize the command into a `heapless::Vec` of capacity `N`
+    ///
+    /// Fails with [`BufferFull`] if the command does not fit into `N` bytes, or if it would
+    /// require command chaining. [`serialized_len`](Self::serialized_len) can be used to
+    /// size `N`.
+    pub fn serialize_to_heapless<const N: usize>(
+        &self,
+        supports_extended: bool,
+    ) -> Result<heapless::Vec<u8, N>, BufferFull> {
+        let mut buffer = heapless::Vec::new();
+        match self.serialize_into(&mut buffer, supports_extended)? {
+            Ok(_) => Ok(buffer),
+            Err(_) => Err(BufferFull),
+        }
+    }
+
+    /// Same as [`serialize_into`](Self::serialize_into), for an [`AsyncWriter`]
+    #[cfg(feature = "async")]
+    pub async fn serialize_into_async<W: AsyncWriter>(
+        &self,
+        writer: &mut W,
+        supports_extended: bool,
+    ) -> Result<Result<usize, CommandBuilder<&[u8]>>, W::Error> {
+        let apdu = self
+            .as_borrowed()
+            .next_apdu(writer.remaining_len(), supports_extended)?;
+        for part in apdu.parts() {
+            writer.write_all(part).await?;
+        }
+        Ok(apdu.into_remainder())
+    }
+
+    /// Start a resumable serialization of the first APDU of the command, for writers that may
+    /// stop accepting data before the APDU is complete
+    ///
+    /// The APDU is computed as for [`serialize_into`](Self::serialize_into) with a writer that
+    /// has `max_len` bytes left. See [`SerializeState`].
+    pub fn serialize_state(
+        &self,
+        max_len: usize,
+        supports_extended: bool,
+    ) -> Result<SerializeState<'_>, BufferFull> {
+        Ok(SerializeState {
+            apdu: self.as_borrowed().next_apdu(max_len, supports_extended)?,
+            written: 0,
+        })
+    }
+
+    /// Iterate over the APDUs of the command, each serialized into a buffer of `N` bytes
+    ///
+    /// All APDUs but the last one have the chaining bit set in CLA. This is equivalent to
+    /// calling [`serialize_into`](Self::serialize_into) on the successive remainders.
+    pub fn chunks<const N: usize>(&self, supports_extended: bool) -> Chunks<'_, N> {
+        Chunks {
+            remaining: Some(self.as_borrowed()),
+            supports_extended,
+        }
+    }
+
+    fn as_borrowed(&self) -> CommandBuilder<&[u8]> {
+        self.with_data(self.data())
+    }
+
+    fn header(&self, class: Class) -> [u8; HEADER_LEN] {
+        [
+            class.into_inner(),
+            self.instruction.into(),
+            self.p1,
+            self.p2,
+        ]
+    }
+
+    fn with_data<'a>(&self, data: &'a [u8]) -> CommandBuilder<&'a [u8]> {
+        CommandBuilder {
+            class: self.class,
+            instruction: self.instruction,
+            p1: self.p1,
+            p2: self.p2,
+            data,
+            le: self.le,
+        }
+    }
+}
+
+impl CommandBuilder<&'static [u8]> {
+    /// Create a case 1 command: no data and no Le field, serialized as the four header bytes
+    ///
+    /// This is equivalent to [`new`](Self::new) with empty data and [`Le::None`].
+    pub fn case1(class: Class, instruction: Instruction, p1: u8, p2: u8) -> Self {
+        Self::new(class, instruction, p1, p2, &[], Le::None)
+    }
+
+    /// Create a READ BINARY command, `00 B0 P1 P2 Le`, reading from `offset` in the current EF
+    ///
+    /// The offset is encoded in P1-P2 and must not exceed `0x7FFF`, as the most significant bit
+    /// selects the short EF identifier form.
+    pub fn read_binary(offset: u16, le: impl Into<Le>) -> Result<Self, Error> {
+        let p12 = binary_offset(offset)?;
+        Ok(Self::new_p12(
+            Class::BASIC,
+            Instruction::ReadBinary,
+            p12,
+            &[],
+            le,
+        ))
+    }
+
+    /// Create a GET DATA command, `00 CA P1 P2 Le`, for the data object `tag`
+    ///
+    /// The tag is split into P1 (high byte) and P2 (low byte).
+    pub fn get_data(tag: u16, le: impl Into<Le>) -> Self {
+        Self::new_p12(Class::BASIC, Instruction::GetDataEven, tag, &[], le)
+    }
+
+    /// Create a VERIFY command without data, `00 20 00 XX`, querying the verification status
+    /// of the reference data (e.g. a PIN) `reference`
+    ///
+    /// The card responds with `9000` if the reference data is already verified, or with `63CX`
+    /// where `X` is the number of remaining tries, see [`Status::as_warning_counter`].
+    ///
+    /// [`Status::as_warning_counter`]: crate::Status::as_warning_counter
+    pub fn verify_status(reference: u8) -> Self {
+        Self::case1(Class::BASIC, Instruction::Verify, 0x00, reference)
+    }
+
+    /// Create a MANAGE CHANNEL command opening a new logical channel, `00 70 00 00 01`
+    ///
+    /// The card assigns the channel number and returns it as the response data, see
+    /// [`ResponseView::opened_channel`](crate::response::ResponseView::opened_channel).
+    pub fn manage_channel_open() -> Self {
+        Self::new(Class::BASIC, Instruction::ManageChannel, 0x00, 0x00, &[], 1)
+    }
+
+    /// Create a MANAGE CHANNEL command closing the logical channel `channel`, `00 70 80 XX`
+    pub fn manage_channel_close(channel: u8) -> Self {
+        Self::case1(Class::BASIC, Instruction::ManageChannel, 0x80, channel)
+    }
+
+    /// Create a GET RESPONSE command, `00 C0 00 00 Le`
+    ///
+    /// `le` is the count from a `61XX` status word
+    /// ([`StatusKind::MoreAvailable`](crate::response::StatusKind::MoreAvailable)), where `0`
+    /// means 256 bytes.
+    pub fn get_response(le: u8) -> Self {
+        let le = match le {
+            0 => Le::Exact(256),
+            le => Le::Exact(le.into()),
+        };
+        Self::new(Class::BASIC, Instruction::GetResponse, 0x00, 0x00, &[], le)
+    }
+}
+
+impl<'a> CommandBuilder<&'a [u8]> {
+    /// Create an UPDATE BINARY command, `00 D6 P1 P2 Lc data`, writing `data` at `offset` in the
+    /// current EF
+    ///
+    /// The offset must not exceed `0x7FFF`, see [`read_binary`](CommandBuilder::read_binary).
+    pub fn update_binary(offset: u16, data: &'a [u8]) -> Result<Self, Error> {
+        let p12 = binary_offset(offset)?;
+        Ok(Self::new_p12(
+            Class::BASIC,
+            Instruction::UpdateBinary,
+            p12,
+            data,
+            0,
+        ))
+    }
+
+    /// Create a PUT DATA command, `00 DA P1 P2 Lc value`, for the data object `tag`
+    ///
+    /// The tag is split into P1 (high byte) and P2 (low byte).
+    pub fn put_data(tag: u16, value: &'a [u8]) -> Self {
+        Self::new_p12(Class::BASIC, Instruction::PutDataEven, tag, value, Le::None)
+    }
+
+    /// Create a VERIFY command, `00 20 00 XX`, with the reference data `pin` (e.g. a PIN) for
+    /// the reference `reference`
+    pub fn verify(reference: u8, pin: &'a [u8]) -> Self {
+        Self::new(
+            Class::BASIC,
+            Instruction::Verify,
+            0x00,
+            reference,
+            pin,
+            Le::None,
+        )
+    }
+
+    /// Create a MANAGE SECURITY ENVIRONMENT command, `00 22 P1 P2 [Lc data]`
+    ///
+    /// For [`MseOperation::Set`], `data` holds the components of the control reference
+    /// template, e.g. the key reference (`83` or `84`) and the algorithm reference (`80`). It is
+    /// usually empty for the other operations.
+    pub fn manage_security_environment(operation: MseOperation, data: &'a [u8]) -> Self {
+        Self::new(
+            Class::BASIC,
+            Instruction::ManageSecurityEnvironment,
+            operation.p1(),
+            operation.p2(),
+            data,
+            Le::None,
+        )
+    }
+
+    /// Create a PERFORM SECURITY OPERATION command, `00 2A P1 P2 Lc data Le`
+    ///
+    /// `data` is the input of the operation and `le` the expected length of its result, e.g.
+    /// the signature length, or [`Le::None`] for the verify operations, which return no data.
+    /// The key and algorithm are usually chosen beforehand with
+    /// [`manage_security_environment`](Self::manage_security_environment).
+    pub fn perform_security_operation(
+        operation: PsoOperation,
+        data: &'a [u8],
+        le: impl Into<Le>,
+    ) -> Self {
+        Self::new_p12(
+            Class::BASIC,
+            Instruction::PerformSecurityOperation,
+            operation.p12(),
+            data,
+            le,
+        )
+    }
+
+    /// Create a SELECT command for the application `aid`, using its full AID as DF name
+    ///
+    /// The command is `00 A4 04 00` with Le = 256. P2 can be changed afterwards to select the
+    /// next occurrence (`0x02`) or to request the FCP (`0x04`), the FMD (`0x08`) or no
+    /// response data (`0x0C`) instead of the FCI.
+    pub fn select_by_aid(aid: &'a Aid) -> Self {
+        Self::new(
+            Class::BASIC,
+            Instruction::Select,
+            0x04,
+            0x00,
+            aid.as_bytes(),
+            Le::Exact(256),
+        )
+    }
+
+    /// Same as [`serialize_into`](Self::serialize_into), with the remainder borrowing the data
+    /// instead of `self`
+    pub(crate) fn serialize_borrowed<W: Writer>(
+        self,
+        writer: &mut W,
+        supports_extended: bool,
+    ) -> Result<Result<usize, CommandBuilder<&'a [u8]>>, W::Error> {
+        let apdu = self.next_apdu(writer.remaining_len(), supports_extended)?;
+        for part in apdu.parts() {
+            writer.write_all(part)?;
+        }
+        Ok(apdu.into_remainder())
+    }
+
+    /// Compute the next APDU to write, given the space left in the writer
+    ///
+    /// This is shared by the sync and async serialization.
+    fn next_apdu(self, available: usize, supports_extended: bool) -> Result<Apdu<'a>, BufferFull> {
+        let data = self.data;
+        let (max_lc, max_le) = match supports_extended {
+            true => (MAX_EXTENDED_LC, MAX_EXTENDED_LE),
+            false => (MAX_SHORT_LC, MAX_SHORT_LE),
+        };
+        let le = self.le.min(max_le);
+
+        if data.len() <= max_lc {
+            let extended = data.len() > MAX_SHORT_LC || le > MAX_SHORT_LE;
+            let apdu = Apdu::new(self.header(self.class), data, le, extended, None);
+            if apdu.len() <= available {
+                return Ok(apdu);
+            }
+        }
+
+        // Command chaining: the intermediate commands do not carry Le
+        let short_capacity = available.saturating_sub(HEADER_LEN + 1).min(MAX_SHORT_LC);
+        let extended_capacity = match supports_extended {
+            true => available
+                .saturating_sub(HEADER_LEN + 3)
+                .min(MAX_EXTENDED_LC),
+            false => 0,
+        };
+        let (capacity, extended) = if extended_capacity > short_capacity {
+            (extended_capacity, true)
+        } else {
+            (short_capacity, false)
+        };
+        if capacity == 0 {
+            return Err(BufferFull);
+        }
+        // setting the chaining bit fails for 0xEF, the command cannot be split then
+        let chained = self.class.with_chaining(true).map_err(|_| BufferFull)?;
+        let (current, rest) = data.split_at(capacity.min(data.len()));
+        if current.is_empty() {
+            // only Le does not fit, chaining would not make progress
+            return Err(BufferFull);
+        }
+        Ok(Apdu::new(
+            self.header(chained),
+            current,
+            0,
+            extended,
+            Some(self.with_data(rest)),
+        ))
+    }
+}
+
+/// Builder with an owned buffer of `N` bytes, for data assembled from several parts
+impl<const N: usize> CommandBuilder<Data<N>> {
+    /// Create a command without data, see [`new`](Self::new) for `le`
+    ///
+    /// Use [`push_data`](Self::push_data) to append the data.
+    pub fn with_capacity(
+        class: Class,
+        instruction: Instruction,
+        p1: u8,
+        p2: u8,
+        le: impl Into<Le>,
+    ) -> Self {
+        Self::new(class, instruction, p1, p2, Data::new(), le)
+    }
+
+    /// Append `data` to the command data
+    ///
+    /// Fails with [`Error::BufferOverflow`] if the data would exceed `N` bytes, in which case
+    /// the command is left unchanged.
+    pub fn push_data(&mut self, data: &[u8]) -> Result<(), Error> {
+        let needed = self.data.len() + data.len();
+        self.data
+            .extend_from_slice(data)
+            .map_err(|_| Error::BufferOverflow {
+                needed,
+                capacity: N,
+            })
+    }
+}
+
+/// Serialize `commands` back to back into `writer`
+///
+/// Each command is serialized as with [`CommandBuilder::serialize_into`]. A command that needs
+/// command chaining is written as its complete chain, as long as the space left in `writer`
+/// allows it. Once `writer` is full, this fails with [`BufferFull`], leaving the APDUs written
+/// so far in `writer`.
+///
+/// The APDUs are not self-delimiting, so the receiver needs the framing of the surrounding
+/// protocol to split them reliably: [`CommandView::parse_stream`](super::CommandView::parse_stream)
+/// can only split them heuristically.
+pub fn serialize_batch<'a, W: Writer>(
+    commands: impl Iterator<Item = super::CommandView<'a>>,
+    writer: &mut W,
+    supports_extended: bool,
+) -> Result<(), W::Error> {
+    for command in commands {
+        let mut command = Some(CommandBuilder::from(command));
+        while let Some(current) = command {
+            let apdu = current.next_apdu(writer.remaining_len(), supports_extended)?;
+            for part in apdu.parts() {
+                writer.write_all(part)?;
+            }
+            command = apdu.remainder;
+        }
+    }
+    Ok(())
+}
+
+fn binary_offset(offset: u16) -> Result<u16, Error> {
+    if offset > MAX_OFFSET {
+        return Err(Error::InvalidOffset);
+    }
+    Ok(offset)
+}
+
+/// Iterator over the APDUs of a command, see [`CommandBuilder::chunks`]
+#[derive(Clone, Debug)]
+pub struct Chunks<'a, const N: usize> {
+    remaining: Option<CommandBuilder<&'a [u8]>>,
+    supports_extended: bool,
+}
+
+impl<const N: usize> Iterator for Chunks<'_, N> {
+    type Item = Result<heapless::Vec<u8, N>, BufferFull>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let apdu = match self.remaining.take()?.next_apdu(N, self.supports_extended) {
+            Ok(apdu) => apdu,
+            Err(err) => return Some(Err(err)),
+        };
+        let mut buffer = heapless::Vec::new();
+        for part in apdu.parts() {
+            // cannot fail, the length of the APDU is at most N
+            buffer.extend_from_slice(part).ok();
+        }
+        self.remaining = apdu.into_remainder().err();
+        Some(Ok(buffer))
+    }
+}
+
+/// Resumable serialization of a single APDU, see [`CommandBuilder::serialize_state`]
+///
+/// [`serialize_into`](CommandBuilder::serialize_into) retries short writes, but fails with
+/// [`BufferFull`] as soon as [`Writer::write`] returns `0`, losing track of the progress.
+/// Instead, [`write_into`](Self::write_into) returns when the writer stops accepting data and
+/// can be called again, with the same or another writer, to continue where it stopped.
+pub struct SerializeState<'a> {
+    apdu: Apdu<'a>,
+    written: usize,
+}
+
+impl<'a> SerializeState<'a> {
+    /// Total length of the APDU
+    pub fn len(&self) -> usize {
+        self.apdu.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of bytes written so far
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    /// Returns `true` if the whole APDU has been written
+    pub fn is_done(&self) -> bool {
+        self.written == self.len()
+    }
+
+    /// Write as much of the rest of the APDU as `writer` accepts, returning the number of
+    /// bytes written by this call
+    pub fn write_into<W: Writer>(&mut self, writer: &mut W) -> Result<usize, W::Error> {
+        let start = self.written;
+        let mut skip = self.written;
+        for part in self.apdu.parts() {
+            if skip >= part.len() {
+                skip -= part.len();
+                continue;
+            }
+            let mut part = &part[skip..];
+            skip = 0;
+            while !part.is_empty() {
+                let written = writer.write(part)?;
+                if written == 0 {
+                    return Ok(self.written - start);
+                }
+                self.written += written;
+                part = &part[written..];
+            }
+        }
+        Ok(self.written - start)
+    }
+
+    /// The rest of the command if it is chained, to be serialized once this APDU is done
+    pub fn into_remainder(self) -> Option<CommandBuilder<&'a [u8]>> {
+        self.apdu.remainder
+    }
+}
+
+/// A single APDU, ready to be written
+struct Apdu<'a> {
+    header: [u8; HEADER_LEN],
+    lc: ([u8; 3], usize),
+    data: &'a [u8],
+    le: ([u8; 3], usize),
+    remainder: Option<CommandBuilder<&'a [u8]>>,
+}
+
+impl<'a> Apdu<'a> {
+    fn new(
+        header: [u8; HEADER_LEN],
+        data: &'a [u8],
+        le: usize,
+        extended: bool,
+        remainder: Option<CommandBuilder<&'a [u8]>>,
+    ) -> Self {
+        let lc = match (data.len(), extended) {
+            (0, _) => ([0; 3], 0),
+            (len, false) => ([len as u8, 0, 0], 1),
+            (len, true) => {
+                let [hi, lo] = (len as u16).to_be_bytes();
+                ([0, hi, lo], 3)
+            }
+        };
+        // 256 and 65536 are encoded as zero
+        let le = match (le, extended, data.is_empty()) {
+            (0, _, _) => ([0; 3], 0),
+            (le, false, _) => ([le as u8, 0, 0], 1),
+            (le, true, true) => {
+                let [hi, lo] = (le as u16).to_be_bytes();
+                ([0, hi, lo], 3)
+            }
+            (le, true, false) => {
+                let [hi, lo] = (le as u16).to_be_bytes();
+                ([hi, lo, 0], 2)
+            }
+        };
+        Self {
+            header,
+            lc,
+            data,
+            le,
+            remainder,
+        }
+    }
+
+    fn parts(&self) -> [&[u8]; 4] {
+        [
+            &self.header,
+            &self.lc.0[..self.lc.1],
+            self.data,
+            &self.le.0[..self.le.1],
+        ]
+    }
+
+    fn len(&self) -> usize {
+        self.parts().iter().map(|part| part.len()).sum()
+    }
+
+    /// The remainder of a chained command, or the length of the last APDU
+    fn into_remainder(self) -> Result<usize, CommandBuilder<&'a [u8]>> {
+        let len = self.len();
+        match self.remainder {
+            Some(remainder) => Err(remainder),
+            None => Ok(len),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::command::CommandView;
+    use crate::writer::SliceWriter;
+    use hex_literal::hex;
+
+    fn builder(data: &[u8], le: usize) -> CommandBuilder<&[u8]> {
+        CommandBuilder::new(
+            Class::try_from(0x00).unwrap(),
+            Instruction::Select,
+            0x04,
+            0x00,
+            data,
+            Le::from_expected(le),
+        )
+    }
+
+    /// Serialize into a heap buffer, large enough for any APDU
+    fn serialize(command: &CommandBuilder<&[u8]>, supports_extended: bool) -> Vec<u8> {
+        let mut buffer = vec![0; 4 + 3 + 65_535 + 2];
+        let mut writer = SliceWriter::new(&mut buffer);
+        let len = command
+            .serialize_into(&mut writer, supports_extended)
+            .unwrap()
+            .unwrap();
+        buffer.truncate(len);
+        buffer
+    }
+
+    #[test]
+    fn short() {
+        assert_eq!(serialize(&builder(&[], 0), false), hex!("00A40400"));
+        assert_eq!(serialize(&builder(&[], 256), false), hex!("00A40400 00"));
+        assert_eq!(serialize(&builder(&[], 16), true), hex!("00A40400 10"));
+        assert_eq!(
+            serialize(&builder(&hex!("0102"), 0), true),
+            hex!("00A40400 02 0102")
+        );
+        assert_eq!(
+            serialize(&builder(&hex!("0102"), 256), true),
+            hex!("00A40400 02 0102 00")
+        );
+    }
+
+    #[test]
+    fn case1() {
+        let command = CommandBuilder::case1(
+            Class::try_from(0x00).unwrap(),
+            Instruction::Select,
+            0x04,
+            0x00,
+        );
+        assert_eq!(command.case(), Case::Case1);
+        assert_eq!(serialize(&command, false), hex!("00A40400"));
+        assert_eq!(serialize(&command, true), hex!("00A40400"));
+    }
+
+    #[test]
+    fn case3() {
+        let command = CommandBuilder::case3(
+            Class::BASIC,
+            Instruction::UpdateBinary,
+            0x00,
+            0x00,
+            &hex!("0102")[..],
+        );
+        assert_eq!(command.case(), Case::Case3);
+        let apdu = serialize(&command, false);
+        assert_eq!(apdu, hex!("00D60000 02 0102"));
+        let view = CommandView::try_from(apdu.as_slice()).unwrap();
+        assert_eq!(view.case(), Case::Case3);
+        assert_eq!(view.le(), Le::None);
+        assert_eq!(view, command);
+
+        let data = [0xAB; 300];
+        let command = CommandBuilder::case3(
+            Class::BASIC,
+            Instruction::UpdateBinary,
+            0x00,
+            0x00,
+            &data[..],
+        );
+        let apdu = serialize(&command, true);
+        assert_eq!(apdu.len(), 4 + 3 + 300);
+        assert_eq!(apdu[4..7], hex!("00 012C"));
+        let view = CommandView::try_from(apdu.as_slice()).unwrap();
+        assert_eq!(view.le(), Le::None);
+        assert_eq!(view.expected(), 0);
+        assert_eq!(view, command);
+    }
+
+    #[test]
+    fn manage_security_environment() {
+        use crate::command::security::{CrtTag, MseUsage};
+
+        let command = CommandBuilder::manage_security_environment(
+            MseOperation::Set(MseUsage::COMPUTATION, CrtTag::DigitalSignature),
+            &hex!("84 01 9C 80 01 07"),
+        );
+        assert_eq!(
+            serialize(&command, false),
+            hex!("0022 41B6 06 8401 9C80 0107")
+        );
+        let command = CommandBuilder::manage_security_environment(MseOperation::Restore(1), &[]);
+        assert_eq!(command.case(), Case::Case1);
+        assert_eq!(serialize(&command, false), hex!("0022 F301"));
+    }
+
+    #[test]
+    fn perform_security_operation() {
+        let digest = [0xAB; 32];
+        let command = CommandBuilder::perform_security_operation(
+            PsoOperation::ComputeDigitalSignature,
+            &digest,
+            256,
+        );
+        assert_eq!(command.case(), Case::Case4);
+        let apdu = serialize(&command, false);
+        assert_eq!(apdu[..5], hex!("002A 9E9A 20"));
+        assert_eq!(apdu[5..37], digest);
+        assert_eq!(apdu[37..], hex!("00"));
+
+        let command = CommandBuilder::perform_security_operation(
+            PsoOperation::VerifyCertificate,
+            &hex!("7F21 00"),
+            0,
+        );
+        assert_eq!(serialize(&command, false), hex!("002A 0092 03 7F2100"));
+
+        let command =
+            CommandBuilder::perform_security_operation(PsoOperation::Decipher, &[0; 300], 256);
+        assert_eq!(serialize(&command, true)[..7], hex!("002A 8086 00 012C"));
+    }
+
+    #[test]
+    fn check_length() {
+        for (data_len, le, short_ok) in [
+            (0, 0, true),
+            (255, 256, true),
+            (256, 0, false),
+            (0, 257, false),
+            (0, 65536, false),
+        ] {
+            let data = [0; 256];
+            let command = builder(&data[..data_len], le);
+            assert_eq!(command.check_length(true), Ok(()));
+            assert_eq!(
+                command.check_length(false).is_ok(),
+                short_ok,
+                "{data_len} {le}"
+            );
+        }
+        let command = CommandBuilder::read_binary(0, 1024).unwrap();
+        assert_eq!(command.check_length(false), Err(Error::ExtendedRequired));
+        // the default behavior is unchanged
+        assert_eq!(serialize(&command, false), hex!("00B00000 00"));
+    }
+
+    #[test]
+    fn encode_short_extended() {
+        let data = [0xAB; 300];
+        for (data_len, le, short, extended) in [
+            (0, 0, &hex!("00A40400")[..], &hex!("00A40400")[..]),
+            (0, 256, &hex!("00A40400 00"), &hex!("00A40400 000100")),
+            (
+                2,
+                0,
+                &hex!("00A40400 02 ABAB"),
+                &hex!("00A40400 000002 ABAB"),
+            ),
+            (
+                1,
+                0x100,
+                &hex!("00A40400 01 AB 00"),
+                &hex!("00A40400 000001 AB 0100"),
+            ),
+        ] {
+            let command = builder(&data[..data_len], le);
+            let encoded = command.encode_short::<16>().unwrap();
+            assert_eq!(encoded, short);
+            let encoded_extended = command.encode_extended::<16>().unwrap();
+            assert_eq!(encoded_extended, extended);
+            // both encodings parse back to the same logical command
+            let view = CommandView::try_from(&*encoded).unwrap();
+            let view_extended = CommandView::try_from(&*encoded_extended).unwrap();
+            assert_eq!(CommandBuilder::from(view), command);
+            assert_eq!(CommandBuilder::from(view_extended), command);
+        }
+
+        let command = builder(&[], 65536);
+        assert_eq!(command.encode_short::<16>(), Err(Error::ExtendedRequired));
+        assert_eq!(
+            command.encode_extended::<16>().unwrap(),
+            hex!("00A40400 000000")
+        );
+        let command = builder(&data, 0);
+        assert_eq!(command.encode_short::<400>(), Err(Error::ExtendedRequired));
+        assert_eq!(command.encode_extended::<400>().unwrap().len(), 307);
+        assert_eq!(
+            command.encode_extended::<300>(),
+            Err(Error::BufferOverflow {
+                needed: 307,
+                capacity: 300
+            })
+        );
+        assert_eq!(
+            builder(&vec![0; 65_536], 0).encode_extended::<16>(),
+            Err(Error::InvalidSliceLength)
+        );
+    }
+
+    #[test]
+    fn push_data() {
+        let mut command = CommandBuilder::<Data<4>>::with_capacity(
+            Class::try_from(0x00).unwrap(),
+            Instruction::Select,
+            0x04,
+            0x00,
+            256,
+        );
+        assert_eq!(command.case(), Case::Case2);
+        command.push_data(&hex!("01")).unwrap();
+        command.push_data(&[]).unwrap();
+        command.push_data(&hex!("0203")).unwrap();
+        assert_eq!(
+            command.push_data(&hex!("0405")),
+            Err(Error::BufferOverflow {
+                needed: 5,
+                capacity: 4
+            })
+        );
+        assert_eq!(command.data(), hex!("010203"));
+        assert_eq!(
+            serialize(&command.as_borrowed(), false),
+            serialize(&builder(&hex!("010203"), 256), false)
+        );
+    }
+
+    #[test]
+    fn p12() {
+        let command = CommandBuilder::new_p12(
+            Class::BASIC,
+            Instruction::Select,
+            0x0400,
+            &hex!("0102")[..],
+            0,
+        );
+        assert_eq!(command, builder(&hex!("0102"), 0));
+        assert_eq!(command.p12(), 0x0400);
+        assert_eq!(CommandBuilder::get_data(0x5FC1, 256).p12(), 0x5FC1);
+    }
+
+    #[test]
+    fn binary() {
+        let command = CommandBuilder::read_binary(0x0102, 0x10).unwrap();
+        assert_eq!(serialize(&command, false), hex!("00B00102 10"));
+        let command = CommandBuilder::read_binary(0x7FFF, 256).unwrap();
+        assert_eq!(serialize(&command, false), hex!("00B07FFF 00"));
+        assert_eq!(
+            CommandBuilder::read_binary(0x8000, 256),
+            Err(Error::InvalidOffset)
+        );
+
+        let command = CommandBuilder::update_binary(0x0010, &hex!("0102")).unwrap();
+        assert_eq!(command.case(), Case::Case3);
+        assert_eq!(serialize(&command, false), hex!("00D60010 02 0102"));
+        assert_eq!(
+            CommandBuilder::update_binary(0xFFFF, &hex!("0102")),
+            Err(Error::InvalidOffset)
+        );
+    }
+
+    #[test]
+    fn data_objects() {
+        let command = CommandBuilder::get_data(0x5F50, 256);
+        assert_eq!(serialize(&command, false), hex!("00CA5F50 00"));
+        let command = CommandBuilder::put_data(0x005E, b"login");
+        assert_eq!(serialize(&command, false), hex!("00DA005E 05 6C6F67696E"));
+    }
+
+    #[test]
+    fn verify() {
+        let command = CommandBuilder::verify(0x81, b"123456");
+        assert_eq!(command.case(), Case::Case3);
+        assert_eq!(serialize(&command, false), hex!("00200081 06 313233343536"));
+        let command = CommandBuilder::verify_status(0x81);
+        assert_eq!(command.case(), Case::Case1);
+        assert_eq!(serialize(&command, false), hex!("00200081"));
+    }
+
+    #[test]
+    fn manage_channel() {
+        let open = CommandBuilder::manage_channel_open();
+        assert_eq!(serialize(&open, false), hex!("00700000 01"));
+        let close = CommandBuilder::manage_channel_close(2);
+        assert_eq!(serialize(&close, false), hex!("00708002"));
+    }
+
+    #[test]
+    fn get_response() {
+        let command = CommandBuilder::get_response(0x10);
+        assert_eq!(command.expected(), 0x10);
+        assert_eq!(serialize(&command, true), hex!("00C00000 10"));
+        let command = CommandBuilder::get_response(0);
+        assert_eq!(command.expected(), 256);
+        assert_eq!(serialize(&command, true), hex!("00C00000 00"));
+    }
+
+    #[test]
+    fn select_by_aid() {
+        let aid = Aid::new(&hex!("A000000308 00001000 0100"));
+        let mut command = CommandBuilder::select_by_aid(&aid);
+        assert_eq!(
+            serialize(&command, false),
+            hex!("00A40400 0B A000000308000010000100 00")
+        );
+        command.p2 = 0x0C;
+        assert_eq!(
+            serialize(&command, false),
+            hex!("00A4040C 0B A000000308000010000100 00")
+        );
+        let command = command
+            .with_class(Class::try_from(0x01).unwrap())
+            .with_p1(0x00)
+            .with_p2(0x02);
+        assert_eq!(
+            serialize(&command, false),
+            hex!("01A40002 0B A000000308000010000100 00")
+        );
+    }
+
+    #[test]
+    fn extended() {
+        assert_eq!(
+            serialize(&builder(&[], 65_536), true),
+            hex!("00A40400 000000")
+        );
+        assert_eq!(serialize(&builder(&[], 257), true), hex!("00A40400 000101"));
+        assert_eq!(
+            serialize(&builder(&hex!("0102"), 257), true),
+            hex!("00A40400 000002 0102 0101")
+        );
+        let data = [0xAB; 256];
+        let serialized = serialize(&builder(&data, 0), true);
+        assert_eq!(serialized[..7], hex!("00A40400 000100"));
+        assert_eq!(serialized.len(), 7 + 256);
+    }
+
+    #[test]
+    fn serialize_state() {
+        /// Accepts at most three bytes per call, and nothing every other call
+        struct Flaky {
+            buffer: heapless::Vec<u8, 64>,
+            calls: usize,
+        }
+
+        impl Writer for Flaky {
+            type Error = BufferFull;
+            fn write(&mut self, data: &[u8]) -> Result<usize, Self::Error> {
+                self.calls += 1;
+                if self.calls.is_multiple_of(2) {
+                    return Ok(0);
+                }
+                Writer::write(&mut self.buffer, &data[..data.len().min(3)])
+            }
+            fn remaining_len(&self) -> usize {
+                usize::MAX
+            }
+        }
+
+        let data = [0xAB; 20];
+        let command = builder(&data, 256);
+        let mut writer = Flaky {
+            buffer: heapless::Vec::new(),
+            calls: 0,
+        };
+        let mut state = command.serialize_state(64, false).unwrap();
+        assert_eq!(state.len(), 26);
+        let mut calls = 0;
+        while !state.is_done() {
+            assert!(state.write_into(&mut writer).unwrap() <= 3);
+            calls += 1;
+        }
+        assert!(calls >= 9);
+        assert_eq!(state.written(), 26);
+        assert_eq!(state.write_into(&mut writer), Ok(0));
+        assert!(state.into_remainder().is_none());
+        assert_eq!(writer.buffer[..], serialize(&command, false));
+
+        let state = command.serialize_state(15, false).unwrap();
+        assert_eq!(state.len(), 15);
+        assert_eq!(state.into_remainder().unwrap().data(), &data[10..]);
+    }
+
+    #[test]
+    fn serialize_to_heapless() {
+        let command = builder(&hex!("0102"), 256);
+        assert_eq!(
+            command.serialize_to_heapless::<8>(false).unwrap(),
+            hex!("00A40400 02 0102 00")
+        );
+        assert_eq!(command.serialize_to_heapless::<7>(false), Err(BufferFull));
+        let data = [0xAB; 256];
+        assert_eq!(
+            builder(&data, 0).serialize_to_heapless::<300>(false),
+            Err(BufferFull)
+        );
+        assert!(builder(&data, 0).serialize_to_heapless::<300>(true).is_ok());
+    }
+
+    #[test]
+    fn prefer_short() {
+        let data = [0xAB; 256];
+        assert_eq!(builder(&data[..255], 256).serialized_len(true), 5 + 255 + 1);
+        assert_eq!(builder(&data, 256).serialized_len(true), 7 + 256 + 2);
+        assert_eq!(builder(&data[..1], 257).serialized_len(true), 7 + 1 + 2);
+        assert_eq!(serialize(&builder(&[], 256), true), hex!("00A40400 00"));
+        assert_eq!(serialize(&builder(&[], 257), true), hex!("00A40400 000101"));
+    }
+
+    #[test]
+    fn written_len() {
+        let mut buffer = heapless::Vec::<u8, 32>::new();
+        let command = builder(&hex!("0102"), 256);
+        assert_eq!(command.serialize_into(&mut buffer, false), Ok(Ok(8)));
+        assert_eq!(command.serialize_into(&mut buffer, false), Ok(Ok(8)));
+        assert_eq!(buffer.len(), 16);
+    }
+
+    #[test]
+    fn extended_max_le() {
+        let command = builder(&hex!("0102"), 65_536);
+        assert_eq!(command.expected(), 65_536);
+        let serialized = serialize(&command, true);
+        assert_eq!(serialized, hex!("00A40400 000002 0102 0000"));
+        let view = CommandView::try_from(serialized.as_slice()).unwrap();
+        assert_eq!(view.expected(), 65_536);
+        assert_eq!(view.le(), Le::Max);
+        assert_eq!(builder(&[], 70_000).expected(), 65_536);
+    }
+
+    #[test]
+    fn le_truncated_without_extended() {
+        assert_eq!(serialize(&builder(&[], 65_536), false), hex!("00A40400 00"));
+    }
+
+    #[test]
+    fn round_trip() {
+        let data = [0xAB; 300];
+        for (len, le) in [(0, 0), (0, 1), (5, 0), (5, 256), (300, 0), (300, 65_536)] {
+            let command = builder(&data[..len], le);
+            let serialized = serialize(&command, true);
+            let view = CommandView::try_from(serialized.as_slice()).unwrap();
+            assert_eq!(view.class(), command.class());
+            assert_eq!(view.instruction(), command.instruction());
+            assert_eq!(view.data(), command.data());
+            assert_eq!(view.expected(), command.expected());
+        }
+    }
+
+    #[test]
+    fn serialized_len() {
+        let data = [0xAB; 300];
+        for supports_extended in [false, true] {
+            for (len, le) in [(0, 0), (0, 256), (0, 257), (5, 0), (5, 256), (300, 65_536)] {
+                let command = builder(&data[..len], le);
+                let mut buffer = heapless::Vec::<u8, 1000>::new();
+                command
+                    .serialize_into(&mut buffer, supports_extended)
+                    .unwrap()
+                    .ok();
+                assert_eq!(command.serialized_len(supports_extended), buffer.len());
+            }
+        }
+        assert_eq!(builder(&[], 0).serialized_len(false), 4);
+        assert_eq!(builder(&data[..5], 256).serialized_len(false), 11);
+        assert_eq!(builder(&data[..5], 257).serialized_len(true), 4 + 3 + 5 + 2);
+    }
+
+    #[test]
+    fn chaining() {
+        let data = [0xAB; 20];
+        let command = builder(&data, 256);
+        let mut buffer = heapless::Vec::<u8, 15>::new();
+        let rem = command
+            .serialize_into(&mut buffer, false)
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(buffer[..5], hex!("10A40400 0A"));
+        assert_eq!(buffer[5..], data[..10]);
+        assert_eq!(rem.data(), &data[10..]);
+
+        let mut buffer = heapless::Vec::<u8, 16>::new();
+        rem.serialize_into(&mut buffer, false).unwrap().unwrap();
+        assert_eq!(buffer[..5], hex!("00A40400 0A"));
+        assert_eq!(buffer[5..15], data[10..]);
+        assert_eq!(buffer[15..], hex!("00"));
+
+        let mut buffer = heapless::Vec::<u8, 5>::new();
+        assert_eq!(command.serialize_into(&mut buffer, false), Err(BufferFull));
+    }
+
+    #[test]
+    fn never_chain_into_ff() {
+        let data = [0; 300];
+        for (cla, chained) in [(0x00, 0x10), (0xE0, 0xF0), (0xEE, 0xFE), (0xFE, 0xFE)] {
+            let command = builder(&data, 0).with_class(Class::try_from(cla).unwrap());
+            let mut buffer = heapless::Vec::<u8, 261>::new();
+            let rem = command
+                .serialize_into(&mut buffer, false)
+                .unwrap()
+                .unwrap_err();
+            assert_eq!(buffer[0], chained);
+            let mut buffer = heapless::Vec::<u8, 261>::new();
+            rem.serialize_into(&mut buffer, false).unwrap().unwrap();
+            assert_eq!(buffer[0], cla);
+        }
+
+        let command = builder(&data, 0).with_class(Class::try_from(0xEF).unwrap());
+        let mut buffer = heapless::Vec::<u8, 261>::new();
+        assert_eq!(command.serialize_into(&mut buffer, false), Err(BufferFull));
+        assert!(buffer.is_empty());
+        assert!(command.requires_chaining(261, false));
+        assert!(command.chunks::<261>(false).next().unwrap().is_err());
+        assert_eq!(
+            serialize(&command, true)[..4],
+            hex!("EFA40400"),
+            "chaining is not needed"
+        );
+    }
+
+    #[test]
+    fn requires_chaining() {
+        let data = [0xAB; 300];
+        let command = builder(&data[..20], 256);
+        assert!(!command.requires_chaining(26, false));
+        assert!(command.requires_chaining(25, false));
+        let command = builder(&data, 0);
+        assert!(command.requires_chaining(1000, false));
+        assert!(!command.requires_chaining(1000, true));
+        assert!(builder(&[], 0).requires_chaining(3, true));
+    }
+
+    #[test]
+    fn chaining_extended() {
+        let data = vec![0xAB; 70_000];
+        let command = builder(&data, 0);
+        assert_eq!(command.serialized_len(true), 7 + 65_535);
+        let mut buffer = vec![0; 70_000];
+        let mut writer = SliceWriter::new(&mut buffer);
+        let rem = command
+            .serialize_into(&mut writer, true)
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(writer.as_slice()[..7], hex!("10A40400 00FFFF"));
+        assert_eq!(writer.len(), 7 + 65_535);
+        assert_eq!(rem.data().len(), 70_000 - 65_535);
+
+        let mut writer = SliceWriter::new(&mut buffer);
+        assert_eq!(rem.serialize_into(&mut writer, true), Ok(Ok(7 + 4465)));
+        assert_eq!(writer.as_slice()[..7], hex!("00A40400 001171"));
+    }
+
+    #[test]
+    fn chaining_without_extended() {
+        let data = [0xAB; 300];
+        let command = builder(&data, 0);
+        let mut buffer = heapless::Vec::<u8, 1000>::new();
+        let rem = command
+            .serialize_into(&mut buffer, false)
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(buffer[..5], hex!("10A40400 FF"));
+        assert_eq!(buffer.len(), 5 + 255);
+        assert_eq!(rem.data(), &data[255..]);
+    }
+
+    #[test]
+    fn le_does_not_fit() {
+        // an extended Le field without data needs 7 bytes, and chaining cannot help
+        let command = builder(&[], 65_536);
+        for available in [5, 6] {
+            let mut buffer = [0; 6];
+            let mut writer = SliceWriter::new(&mut buffer[..available]);
+            assert_eq!(command.serialize_into(&mut writer, true), Err(BufferFull));
+        }
+        let mut chunks = command.chunks::<6>(true);
+        assert_eq!(chunks.next(), Some(Err(BufferFull)));
+        assert_eq!(chunks.next(), None);
+        assert_eq!(command.serialize_to_heapless::<7>(true).unwrap().len(), 7);
+        assert_eq!(
+            command.serialize_to_heapless::<6>(false).unwrap(),
+            hex!("00A40400 00")
+        );
+    }
+
+    #[test]
+    fn serialize_batch() {
+        let apdus = [
+            &hex!("00A40400 02 A000 00")[..],
+            &hex!("00B00000"),
+            &hex!("00CA 5FC1 000000"),
+        ];
+        let views = || {
+            apdus
+                .iter()
+                .map(|apdu| CommandView::try_from(*apdu).unwrap())
+        };
+        let mut buffer = heapless::Vec::<u8, 32>::new();
+        super::serialize_batch(views(), &mut buffer, true).unwrap();
+        assert_eq!(buffer, hex!("00A40400 02 A000 00 00B00000 00CA5FC1 000000"));
+
+        // the command is re-encoded, Le is truncated without extended length support
+        let mut buffer = heapless::Vec::<u8, 32>::new();
+        super::serialize_batch(views(), &mut buffer, false).unwrap();
+        assert_eq!(buffer, hex!("00A40400 02 A000 00 00B00000 00CA5FC1 00"));
+
+        let mut buffer = heapless::Vec::<u8, 12>::new();
+        assert_eq!(
+            super::serialize_batch(views(), &mut buffer, false),
+            Err(BufferFull)
+        );
+        assert_eq!(buffer, hex!("00A40400 02 A000 00 00B00000"));
+
+        // chained commands are written completely
+        let data = [0xAB; 300];
+        let long = builder(&data, 0)
+            .serialize_to_heapless::<310>(true)
+            .unwrap();
+        let mut buffer = heapless::Vec::<u8, 320>::new();
+        super::serialize_batch(
+            [CommandView::try_from(&*long).unwrap()].into_iter(),
+            &mut buffer,
+            false,
+        )
+        .unwrap();
+        assert_eq!(buffer.len(), 310);
+        assert_eq!(buffer[..5], hex!("10A40400 FF"));
+        assert_eq!(buffer[260..265], hex!("00A40400 2D"));
+    }
+
+    #[test]
+    fn chunks() {
+        let data = [0xAB; 20];
+        let command = builder(&data, 256);
+        let chunks: Vec<_> = command.chunks::<12>(false).map(Result::unwrap).collect();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0][..5], hex!("10A40400 07"));
+        assert_eq!(chunks[1][..5], hex!("10A40400 07"));
+        assert_eq!(chunks[2][..5], hex!("00A40400 06"));
+        assert_eq!(chunks[2][11..], hex!("00"));
+        let reassembled: Vec<u8> = chunks
+            .iter()
+            .flat_map(|chunk| &chunk[5..][..chunk[4] as usize])
+            .copied()
+            .collect();
+        assert_eq!(reassembled, data);
+
+        let mut chunks = command.chunks::<5>(false);
+        assert_eq!(chunks.next(), Some(Err(BufferFull)));
+        assert_eq!(chunks.next(), None);
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn serialize_async() {
+        use core::future::Future;
+
+        struct AsyncBuffer(heapless::Vec<u8, 64>);
+
+        impl AsyncWriter for AsyncBuffer {
+            type Error = BufferFull;
+            async fn write(&mut self, data: &[u8]) -> Result<usize, Self::Error> {
+                Writer::write(&mut self.0, data)
+            }
+            fn remaining_len(&self) -> usize {
+                Writer::remaining_len(&self.0)
+            }
+        }
+
+        let data = [0xAB; 20];
+        let command = builder(&data, 256);
+        let mut writer = AsyncBuffer(heapless::Vec::new());
+        {
+            let future = core::pin::pin!(command.serialize_into_async(&mut writer, false));
+            let mut context = core::task::Context::from_waker(core::task::Waker::noop());
+            let core::task::Poll::Ready(result) = future.poll(&mut context) else {
+                panic!("writer never returns pending");
+            };
+            result.unwrap().unwrap();
+        }
+        assert_eq!(writer.0[..], serialize(&command, false));
+    }
+}
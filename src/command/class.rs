@@ -9,14 +9,14 @@
 // - secure messaging indication (none, two standard, proprietary)
 // - logical channel number
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Class {
     cla: u8,
     range: Range,
     // secure_messaging: SecureMessaging,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 pub enum SecureMessaging {
     None = 0,
     Proprietary = 1,
@@ -31,7 +31,7 @@ impl SecureMessaging {
     }
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 pub enum Chain {
     LastOrOnly,
     NotTheLast,
@@ -51,6 +51,40 @@ impl Chain {
 }
 
 impl Class {
+    /// `0x00`: channel 0, no secure messaging, no chaining
+    pub(crate) const BASIC: Self = Self {
+        cla: 0,
+        range: Range::Interindustry(Interindustry::First),
+    };
+
+    /// Build an interindustry class from its components
+    ///
+    /// Fails if the channel is greater than 19, or if the secure messaging indication cannot be
+    /// represented for this channel (see [`with_secure_messaging`](Self::with_secure_messaging)).
+    pub fn interindustry(
+        channel: u8,
+        secure_messaging: SecureMessaging,
+        chaining: bool,
+    ) -> Result<Self, InvalidClass> {
+        Self::BASIC
+            .with_channel(channel)
+            .map_err(|_| InvalidClass {})?
+            .with_secure_messaging(secure_messaging)
+            .map_err(|_| InvalidClass {})?
+            .with_chaining(chaining)
+    }
+
+    /// Build a proprietary class (`0x80` to `0xFE`)
+    pub fn proprietary(cla: u8) -> Result<Self, InvalidClass> {
+        match Self::try_from(cla)? {
+            class @ Self {
+                range: Range::Proprietary,
+                ..
+            } => Ok(class),
+            _ => Err(InvalidClass {}),
+        }
+    }
+
     #[inline]
     pub fn into_inner(self) -> u8 {
         self.cla
@@ -72,7 +106,7 @@ impl Class {
                     0b11 => SecureMessaging::Authenticated,
                     _ => unreachable!(),
                 },
-                Interindustry::Further => match (self.cla >> 5) != 0 {
+                Interindustry::Further => match (self.cla >> 5) & 1 != 0 {
                     true => SecureMessaging::Standard,
                     false => SecureMessaging::None,
                 },
@@ -82,6 +116,31 @@ impl Class {
         }
     }
 
+    /// Change the secure messaging indication
+    ///
+    /// The first interindustry classes (channels 0 to 3) support all variants except `Unknown`.
+    /// The further interindustry classes (channels 4 to 19) only support `None` and `Standard`.
+    /// Reserved and proprietary classes do not support secure messaging indication.
+    pub fn with_secure_messaging(
+        self,
+        secure_messaging: SecureMessaging,
+    ) -> Result<Self, InvalidSecureMessaging> {
+        let cla = match (self.range, secure_messaging) {
+            (_, SecureMessaging::Unknown) => return Err(InvalidSecureMessaging {}),
+            (Range::Interindustry(Interindustry::First), sm) => {
+                (self.cla & !0b1100) | ((sm as u8) << 2)
+            }
+            (Range::Interindustry(Interindustry::Further), SecureMessaging::None) => {
+                self.cla & !(1 << 5)
+            }
+            (Range::Interindustry(Interindustry::Further), SecureMessaging::Standard) => {
+                self.cla | (1 << 5)
+            }
+            _ => return Err(InvalidSecureMessaging {}),
+        };
+        Self::try_from(cla).map_err(|_| InvalidSecureMessaging {})
+    }
+
     #[inline]
     pub fn chain(&self) -> Chain {
         if self.cla & (1 << 4) != 0 {
@@ -91,14 +150,70 @@ impl Class {
         }
     }
 
+    /// Set or clear the command chaining bit (bit 5, `0x10`)
+    ///
+    /// Setting the bit fails for `0xEF`, as the resulting class byte `0xFF` is invalid.
+    #[inline]
+    pub fn with_chaining(self, chaining: bool) -> Result<Self, InvalidClass> {
+        match chaining {
+            true => Self::try_from(self.cla | (1 << 4)),
+            false => Self::try_from(self.cla & !(1 << 4)),
+        }
+    }
+
+    /// Class byte with the command chaining bit cleared
+    #[inline]
+    pub(crate) fn without_chaining(self) -> u8 {
+        self.cla & !(1 << 4)
+    }
+
+    /// Logical channel number, from 0 to 19, `None` for reserved and proprietary classes
     #[inline]
     pub fn channel(&self) -> Option<u8> {
         Some(match self.range() {
             Range::Interindustry(Interindustry::First) => self.cla & 0b11,
-            Range::Interindustry(Interindustry::Further) => (4 + self.cla) & 0b111,
+            Range::Interindustry(Interindustry::Further) => 4 + (self.cla & 0b1111),
             _ => return None,
         })
     }
+
+    /// Change the logical channel number
+    ///
+    /// Channels 0 to 3 use the first interindustry encoding, channels 4 to 19 the further
+    /// interindustry encoding. Command chaining is kept, as well as secure messaging, which
+    /// fails if it cannot be represented in the target encoding (proprietary secure messaging
+    /// and an authenticated command header are only available on channels 0 to 3). Reserved and proprietary classes have no channel.
+    pub fn with_channel(self, channel: u8) -> Result<Self, InvalidChannel> {
+        let chaining = self.cla & (1 << 4);
+        let secure_messaging = self.secure_messaging();
+        let cla = match (self.range, channel) {
+            (Range::Interindustry(Interindustry::Reserved) | Range::Proprietary, _) => {
+                return Err(InvalidChannel {})
+            }
+            (_, 0..=3) => {
+                let sm = match secure_messaging {
+                    SecureMessaging::None => 0b00,
+                    SecureMessaging::Proprietary => 0b01,
+                    SecureMessaging::Standard => 0b10,
+                    SecureMessaging::Authenticated => 0b11,
+                    SecureMessaging::Unknown => return Err(InvalidChannel {}),
+                };
+                chaining | (sm << 2) | channel
+            }
+            (_, 4..=19) => {
+                let sm = match secure_messaging {
+                    SecureMessaging::None => 0,
+                    SecureMessaging::Standard => 1 << 5,
+                    SecureMessaging::Proprietary
+                    | SecureMessaging::Authenticated
+                    | SecureMessaging::Unknown => return Err(InvalidChannel {}),
+                };
+                0b0100_0000 | sm | chaining | (channel - 4)
+            }
+            _ => return Err(InvalidChannel {}),
+        };
+        Self::try_from(cla).map_err(|_| InvalidChannel {})
+    }
 }
 
 impl TryFrom<u8> for Class {
@@ -111,6 +226,42 @@ impl TryFrom<u8> for Class {
     }
 }
 
+/// Generates any class byte except `0xFF`
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Class {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Self::try_from(u.int_in_range(0x00..=0xFE)?).map_err(|_| arbitrary::Error::IncorrectFormat)
+    }
+}
+
+/// Serialized as the class byte
+#[cfg(feature = "serde")]
+impl serde::Serialize for Class {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.cla)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Class {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let cla = u8::deserialize(deserializer)?;
+        Self::try_from(cla).map_err(|_| {
+            serde::de::Error::invalid_value(
+                serde::de::Unexpected::Unsigned(cla.into()),
+                &"a class byte other than 0xFF",
+            )
+        })
+    }
+}
+
+impl From<Class> for u8 {
+    #[inline]
+    fn from(class: Class) -> u8 {
+        class.cla
+    }
+}
+
 // impl core::ops::Deref for Class {
 //     type Target = u8;
 //     fn deref(&self) -> &Self::Target {
@@ -118,13 +269,13 @@ impl TryFrom<u8> for Class {
 //     }
 // }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 pub enum Range {
     Interindustry(Interindustry),
     Proprietary,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 pub enum Interindustry {
     First,
     Further,
@@ -134,6 +285,14 @@ pub enum Interindustry {
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub struct InvalidClass {}
 
+/// Error returned by [`Class::with_channel`]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct InvalidChannel {}
+
+/// Error returned by [`Class::with_secure_messaging`]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct InvalidSecureMessaging {}
+
 impl TryFrom<u8> for Range {
     type Error = InvalidClass;
 
@@ -153,3 +312,157 @@ impl TryFrom<u8> for Range {
         Ok(range)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn class(cla: u8) -> Class {
+        Class::try_from(cla).unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        for cla in 0..=0xFE {
+            assert_eq!(Class::try_from(cla).map(u8::from), Ok(cla));
+        }
+        assert_eq!(Class::try_from(0xFF), Err(InvalidClass {}));
+        assert_eq!(class(0x80).range(), Range::Proprietary);
+        assert_eq!(class(0x84).range(), Range::Proprietary);
+        assert_eq!(
+            class(0x20).range(),
+            Range::Interindustry(Interindustry::Reserved)
+        );
+    }
+
+    #[test]
+    fn channel() {
+        assert_eq!(class(0x00).channel(), Some(0));
+        assert_eq!(class(0x03).channel(), Some(3));
+        assert_eq!(class(0x40).channel(), Some(4));
+        assert_eq!(class(0x4F).channel(), Some(19));
+        assert_eq!(class(0x6F).channel(), Some(19));
+        assert_eq!(class(0x20).channel(), None);
+        assert_eq!(class(0x80).channel(), None);
+    }
+
+    #[test]
+    fn secure_messaging() {
+        assert_eq!(class(0x00).secure_messaging(), SecureMessaging::None);
+        assert_eq!(class(0x04).secure_messaging(), SecureMessaging::Proprietary);
+        assert_eq!(class(0x08).secure_messaging(), SecureMessaging::Standard);
+        assert_eq!(
+            class(0x0C).secure_messaging(),
+            SecureMessaging::Authenticated
+        );
+        assert_eq!(class(0x40).secure_messaging(), SecureMessaging::None);
+        assert_eq!(class(0x60).secure_messaging(), SecureMessaging::Standard);
+        assert_eq!(class(0x80).secure_messaging(), SecureMessaging::Unknown);
+    }
+
+    #[test]
+    fn with_secure_messaging() {
+        for sm in [
+            SecureMessaging::None,
+            SecureMessaging::Proprietary,
+            SecureMessaging::Standard,
+            SecureMessaging::Authenticated,
+        ] {
+            let with_sm = class(0x13).with_secure_messaging(sm).unwrap();
+            assert_eq!(with_sm.secure_messaging(), sm);
+            assert_eq!(with_sm.channel(), Some(3));
+            assert!(with_sm.chain().not_the_last());
+        }
+        assert_eq!(
+            class(0x45)
+                .with_secure_messaging(SecureMessaging::Standard)
+                .unwrap()
+                .into_inner(),
+            0x65
+        );
+        assert_eq!(
+            class(0x65)
+                .with_secure_messaging(SecureMessaging::None)
+                .unwrap()
+                .into_inner(),
+            0x45
+        );
+        assert_eq!(
+            class(0x45).with_secure_messaging(SecureMessaging::Proprietary),
+            Err(InvalidSecureMessaging {})
+        );
+        assert_eq!(
+            class(0x80).with_secure_messaging(SecureMessaging::None),
+            Err(InvalidSecureMessaging {})
+        );
+    }
+
+    #[test]
+    fn constructors() {
+        let interindustry = |channel, sm, chaining| {
+            Class::interindustry(channel, sm, chaining).map(Class::into_inner)
+        };
+        assert_eq!(interindustry(0, SecureMessaging::None, false), Ok(0x00));
+        assert_eq!(
+            interindustry(3, SecureMessaging::Authenticated, true),
+            Ok(0x1F)
+        );
+        assert_eq!(interindustry(4, SecureMessaging::None, false), Ok(0x40));
+        assert_eq!(interindustry(19, SecureMessaging::Standard, true), Ok(0x7F));
+        assert_eq!(
+            interindustry(5, SecureMessaging::Proprietary, false),
+            Err(InvalidClass {})
+        );
+        assert_eq!(
+            interindustry(20, SecureMessaging::None, false),
+            Err(InvalidClass {})
+        );
+
+        assert_eq!(Class::proprietary(0x80).map(Class::into_inner), Ok(0x80));
+        assert_eq!(Class::proprietary(0xFE).map(Class::into_inner), Ok(0xFE));
+        assert_eq!(Class::proprietary(0xFF), Err(InvalidClass {}));
+        assert_eq!(Class::proprietary(0x00), Err(InvalidClass {}));
+    }
+
+    #[test]
+    fn chaining() {
+        assert!(class(0x00).chain().last_or_only());
+        assert!(class(0x10).chain().not_the_last());
+
+        let chained = class(0x0C).with_chaining(true).unwrap();
+        assert_eq!(chained.into_inner(), 0x1C);
+        assert!(chained.chain().not_the_last());
+        assert_eq!(chained.with_chaining(false).unwrap().into_inner(), 0x0C);
+        assert_eq!(class(0x80).with_chaining(true).unwrap().into_inner(), 0x90);
+        assert_eq!(class(0xEF).with_chaining(true), Err(InvalidClass {}));
+        assert_eq!(class(0xEF).with_chaining(false), Ok(class(0xEF)));
+
+        // boundary classes around 0xFF
+        assert_eq!(Class::try_from(0xFF), Err(InvalidClass {}));
+        assert_eq!(Class::proprietary(0xFF), Err(InvalidClass {}));
+        assert_eq!(class(0xFE).with_chaining(true), Ok(class(0xFE)));
+        assert_eq!(class(0xFE).with_chaining(false), Ok(class(0xEE)));
+        assert_eq!(class(0xEE).with_chaining(true), Ok(class(0xFE)));
+        assert_eq!(class(0x6F).with_chaining(true), Ok(class(0x7F)));
+    }
+
+    #[test]
+    fn with_channel() {
+        for channel in 0..=19 {
+            let with_channel = class(0x00).with_channel(channel).unwrap();
+            assert_eq!(with_channel.channel(), Some(channel));
+        }
+        assert_eq!(class(0x00).with_channel(20), Err(InvalidChannel {}));
+        assert_eq!(class(0x80).with_channel(1), Err(InvalidChannel {}));
+
+        // chaining and secure messaging are kept
+        assert_eq!(class(0x18).with_channel(2).unwrap().into_inner(), 0x1A);
+        assert_eq!(class(0x18).with_channel(5).unwrap().into_inner(), 0x71);
+        assert_eq!(class(0x71).with_channel(1).unwrap().into_inner(), 0x19);
+        assert_eq!(class(0x04).with_channel(4), Err(InvalidChannel {}));
+        for channel in 4..=19 {
+            assert_eq!(class(0x0C).with_channel(channel), Err(InvalidChannel {}));
+        }
+        assert_eq!(class(0x0C).with_channel(3).unwrap().into_inner(), 0x0F);
+    }
+}
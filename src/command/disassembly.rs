@@ -0,0 +1,103 @@
+use core::fmt;
+
+use super::class::{Range, SecureMessaging};
+use super::CommandView;
+
+/// Multi-line breakdown of a command APDU, see [`CommandView::disassemble`]
+///
+/// ```text
+/// CLA  10: channel 0, no secure messaging, chained
+/// INS  A4: SELECT
+/// P1   04
+/// P2   00
+/// Lc   2
+/// Data 0102
+/// Le   256
+/// ```
+#[derive(Clone, Debug)]
+pub struct Disassembly<'a> {
+    command: CommandView<'a>,
+}
+
+impl<'a> CommandView<'a> {
+    /// Describe the command field by field, for debugging
+    pub fn disassemble(&self) -> Disassembly<'a> {
+        Disassembly {
+            command: self.clone(),
+        }
+    }
+}
+
+impl fmt::Display for Disassembly<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let command = &self.command;
+        let class = command.class();
+        write!(f, "CLA  {:02X}: ", class.into_inner())?;
+        match (class.range(), class.channel()) {
+            (Range::Proprietary, _) => f.write_str("proprietary")?,
+            (_, None) => f.write_str("reserved")?,
+            (_, Some(channel)) => {
+                write!(f, "channel {channel}, ")?;
+                match class.secure_messaging() {
+                    SecureMessaging::None => f.write_str("no secure messaging")?,
+                    SecureMessaging::Proprietary => f.write_str("proprietary secure messaging")?,
+                    SecureMessaging::Standard => f.write_str("secure messaging")?,
+                    SecureMessaging::Authenticated => {
+                        f.write_str("secure messaging, authenticated header")?
+                    }
+                    SecureMessaging::Unknown => f.write_str("unknown secure messaging")?,
+                }
+                if command.is_chained() {
+                    f.write_str(", chained")?;
+                }
+            }
+        }
+        writeln!(f)?;
+        let ins = u8::from(command.instruction());
+        match command.instruction().name() {
+            Some(name) => writeln!(f, "INS  {ins:02X}: {name}")?,
+            None => writeln!(f, "INS  {ins:02X}")?,
+        }
+        writeln!(f, "P1   {:02X}", command.p1)?;
+        write!(f, "P2   {:02X}", command.p2)?;
+        if !command.data().is_empty() {
+            write!(f, "\nLc   {}\nData ", command.lc())?;
+            for byte in command.data() {
+                write!(f, "{byte:02X}")?;
+            }
+        }
+        if command.expected() != 0 {
+            write!(f, "\nLe   {}", command.expected())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn disassemble() {
+        let apdu = hex!("10A40400 02 0102 00");
+        let view = CommandView::try_from(&apdu[..]).unwrap();
+        assert_eq!(
+            view.disassemble().to_string(),
+            "CLA  10: channel 0, no secure messaging, chained\n\
+             INS  A4: SELECT\n\
+             P1   04\n\
+             P2   00\n\
+             Lc   2\n\
+             Data 0102\n\
+             Le   256"
+        );
+
+        let apdu = hex!("80500000");
+        let view = CommandView::try_from(&apdu[..]).unwrap();
+        assert_eq!(
+            view.disassemble().to_string(),
+            "CLA  80: proprietary\nINS  50\nP1   00\nP2   00"
+        );
+    }
+}
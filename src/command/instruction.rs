@@ -18,25 +18,158 @@
 //     }
 // }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Instruction {
     Select,
+    /// GET DATA with odd instruction byte (`0xCB`), BER-TLV encoded data field
     GetData,
+    /// GET DATA with even instruction byte (`0xCA`), data object in P1-P2
+    GetDataEven,
     Verify,
     ChangeReferenceData,
     ResetRetryCounter,
     GeneralAuthenticate,
+    /// PUT DATA with odd instruction byte (`0xDB`), BER-TLV encoded data field
     PutData,
+    /// PUT DATA with even instruction byte (`0xDA`), data object in P1-P2
+    PutDataEven,
     GenerateAsymmetricKeyPair,
     GetResponse,
     ReadBinary,
     WriteBinary,
+    UpdateBinary,
+    EraseBinary,
+    SearchBinary,
+    ReadRecord,
+    WriteRecord,
+    UpdateRecord,
+    AppendRecord,
+    SearchRecord,
+    ManageSecurityEnvironment,
+    PerformSecurityOperation,
+    DisableVerificationRequirement,
+    EnableVerificationRequirement,
+    ExternalAuthenticate,
+    InternalAuthenticate,
+    GetChallenge,
+    ManageChannel,
+    Envelope,
+    ActivateFile,
+    DeactivateFile,
+    CreateFile,
+    DeleteFile,
+    TerminateDf,
+    TerminateCard,
     // Unknown(BinaryInstruction),
     Unknown(u8),
 }
 
 pub struct UnknownInstruction {}
 
+impl Instruction {
+    /// The raw instruction byte
+    pub fn code(self) -> u8 {
+        self.into()
+    }
+
+    /// Returns `true` if the instruction is not one of the interindustry instructions of
+    /// ISO 7816-4 known to this crate
+    ///
+    /// Note that ISO 7816-4 does not reserve a range of instruction bytes for proprietary use:
+    /// any instruction is proprietary when sent with a proprietary class, see
+    /// [`Class::range`](super::class::Class::range).
+    pub fn is_proprietary(self) -> bool {
+        matches!(self, Self::Unknown(_))
+    }
+
+    /// Decode an instruction, rejecting the ones without a named variant
+    ///
+    /// Unlike `From<u8>`, this never returns [`Instruction::Unknown`]: the raw byte is returned
+    /// as error instead.
+    pub fn try_from_known(ins: u8) -> Result<Self, u8> {
+        match Self::from(ins) {
+            Self::Unknown(ins) => Err(ins),
+            instruction => Ok(instruction),
+        }
+    }
+
+    /// Command name as used in ISO 7816-4, `None` for unknown instructions
+    pub fn name(self) -> Option<&'static str> {
+        Some(match self {
+            Instruction::Select => "SELECT",
+            Instruction::GetData => "GET DATA",
+            Instruction::GetDataEven => "GET DATA",
+            Instruction::Verify => "VERIFY",
+            Instruction::ChangeReferenceData => "CHANGE REFERENCE DATA",
+            Instruction::ResetRetryCounter => "RESET RETRY COUNTER",
+            Instruction::GeneralAuthenticate => "GENERAL AUTHENTICATE",
+            Instruction::PutData => "PUT DATA",
+            Instruction::PutDataEven => "PUT DATA",
+            Instruction::GenerateAsymmetricKeyPair => "GENERATE ASYMMETRIC KEY PAIR",
+            Instruction::GetResponse => "GET RESPONSE",
+            Instruction::ReadBinary => "READ BINARY",
+            Instruction::WriteBinary => "WRITE BINARY",
+            Instruction::UpdateBinary => "UPDATE BINARY",
+            Instruction::EraseBinary => "ERASE BINARY",
+            Instruction::SearchBinary => "SEARCH BINARY",
+            Instruction::ReadRecord => "READ RECORD",
+            Instruction::WriteRecord => "WRITE RECORD",
+            Instruction::UpdateRecord => "UPDATE RECORD",
+            Instruction::AppendRecord => "APPEND RECORD",
+            Instruction::SearchRecord => "SEARCH RECORD",
+            Instruction::ManageSecurityEnvironment => "MANAGE SECURITY ENVIRONMENT",
+            Instruction::PerformSecurityOperation => "PERFORM SECURITY OPERATION",
+            Instruction::DisableVerificationRequirement => "DISABLE VERIFICATION REQUIREMENT",
+            Instruction::EnableVerificationRequirement => "ENABLE VERIFICATION REQUIREMENT",
+            Instruction::ExternalAuthenticate => "EXTERNAL AUTHENTICATE",
+            Instruction::InternalAuthenticate => "INTERNAL AUTHENTICATE",
+            Instruction::GetChallenge => "GET CHALLENGE",
+            Instruction::ManageChannel => "MANAGE CHANNEL",
+            Instruction::Envelope => "ENVELOPE",
+            Instruction::ActivateFile => "ACTIVATE FILE",
+            Instruction::DeactivateFile => "DEACTIVATE FILE",
+            Instruction::CreateFile => "CREATE FILE",
+            Instruction::DeleteFile => "DELETE FILE",
+            Instruction::TerminateDf => "TERMINATE DF",
+            Instruction::TerminateCard => "TERMINATE CARD",
+            Instruction::Unknown(_) => return None,
+        })
+    }
+}
+
+impl core::fmt::Display for Instruction {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "{name} ({:02X})", u8::from(*self)),
+            None => write!(f, "INS(0x{:02X})", u8::from(*self)),
+        }
+    }
+}
+
+/// Generates the instruction of an arbitrary byte, so that `Unknown` never holds a known value
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Instruction {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        u8::arbitrary(u).map(Self::from)
+    }
+}
+
+/// Serialized as the instruction byte, so that unknown instructions round-trip
+#[cfg(feature = "serde")]
+impl serde::Serialize for Instruction {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8((*self).into())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Instruction {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        u8::deserialize(deserializer).map(Self::from)
+    }
+}
+
 impl From<u8> for Instruction {
     fn from(ins: u8) -> Self {
         match ins {
@@ -51,6 +184,31 @@ impl From<u8> for Instruction {
             0xdb => Instruction::PutData,
             0xb0 => Instruction::ReadBinary,
             0xd0 => Instruction::WriteBinary,
+            0xca => Instruction::GetDataEven,
+            0xda => Instruction::PutDataEven,
+            0xd6 => Instruction::UpdateBinary,
+            0x0e => Instruction::EraseBinary,
+            0xa0 => Instruction::SearchBinary,
+            0xb2 => Instruction::ReadRecord,
+            0xd2 => Instruction::WriteRecord,
+            0xdc => Instruction::UpdateRecord,
+            0xe2 => Instruction::AppendRecord,
+            0xa2 => Instruction::SearchRecord,
+            0x22 => Instruction::ManageSecurityEnvironment,
+            0x2a => Instruction::PerformSecurityOperation,
+            0x26 => Instruction::DisableVerificationRequirement,
+            0x28 => Instruction::EnableVerificationRequirement,
+            0x82 => Instruction::ExternalAuthenticate,
+            0x88 => Instruction::InternalAuthenticate,
+            0x84 => Instruction::GetChallenge,
+            0x70 => Instruction::ManageChannel,
+            0xc2 => Instruction::Envelope,
+            0x44 => Instruction::ActivateFile,
+            0x04 => Instruction::DeactivateFile,
+            0xe0 => Instruction::CreateFile,
+            0xe4 => Instruction::DeleteFile,
+            0xe6 => Instruction::TerminateDf,
+            0xfe => Instruction::TerminateCard,
             ins => Instruction::Unknown(ins),
         }
     }
@@ -70,6 +228,31 @@ impl From<Instruction> for u8 {
             Instruction::PutData => 0xdb,
             Instruction::ReadBinary => 0xb0,
             Instruction::WriteBinary => 0xd0,
+            Instruction::GetDataEven => 0xca,
+            Instruction::PutDataEven => 0xda,
+            Instruction::UpdateBinary => 0xd6,
+            Instruction::EraseBinary => 0x0e,
+            Instruction::SearchBinary => 0xa0,
+            Instruction::ReadRecord => 0xb2,
+            Instruction::WriteRecord => 0xd2,
+            Instruction::UpdateRecord => 0xdc,
+            Instruction::AppendRecord => 0xe2,
+            Instruction::SearchRecord => 0xa2,
+            Instruction::ManageSecurityEnvironment => 0x22,
+            Instruction::PerformSecurityOperation => 0x2a,
+            Instruction::DisableVerificationRequirement => 0x26,
+            Instruction::EnableVerificationRequirement => 0x28,
+            Instruction::ExternalAuthenticate => 0x82,
+            Instruction::InternalAuthenticate => 0x88,
+            Instruction::GetChallenge => 0x84,
+            Instruction::ManageChannel => 0x70,
+            Instruction::Envelope => 0xc2,
+            Instruction::ActivateFile => 0x44,
+            Instruction::DeactivateFile => 0x04,
+            Instruction::CreateFile => 0xe0,
+            Instruction::DeleteFile => 0xe4,
+            Instruction::TerminateDf => 0xe6,
+            Instruction::TerminateCard => 0xfe,
             Instruction::Unknown(ins) => ins,
         }
     }
@@ -96,3 +279,78 @@ impl From<Instruction> for u8 {
 //         Ok(instruction)
 //     }
 // }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for ins in 0..=u8::MAX {
+            assert_eq!(u8::from(Instruction::from(ins)), ins);
+        }
+        assert_eq!(
+            Instruction::from(0x22),
+            Instruction::ManageSecurityEnvironment
+        );
+        assert_eq!(
+            Instruction::from(0x2a),
+            Instruction::PerformSecurityOperation
+        );
+        assert_eq!(Instruction::from(0xca), Instruction::GetDataEven);
+        assert_eq!(Instruction::from(0xcb), Instruction::GetData);
+        assert_eq!(Instruction::from(0xd6), Instruction::UpdateBinary);
+        assert_eq!(Instruction::from(0x12), Instruction::Unknown(0x12));
+    }
+
+    #[test]
+    fn hash() {
+        use std::collections::HashMap;
+
+        let class = crate::command::class::Class::try_from(0x00).unwrap();
+        let mut routes = HashMap::new();
+        routes.insert((class, Instruction::Select), "select");
+        routes.insert((class, Instruction::Verify), "verify");
+        assert_eq!(
+            routes.get(&(class, Instruction::from(0xa4))),
+            Some(&"select")
+        );
+
+        let mut statuses = HashMap::new();
+        statuses.insert(crate::Status::SUCCESS, "ok");
+        assert_eq!(statuses.get(&crate::Status::from(0x9000)), Some(&"ok"));
+    }
+
+    #[test]
+    fn code() {
+        for ins in 0..=u8::MAX {
+            assert_eq!(Instruction::from(ins).code(), ins);
+        }
+        assert!(!Instruction::Select.is_proprietary());
+        assert!(Instruction::from(0x50).is_proprietary());
+    }
+
+    #[test]
+    fn try_from_known() {
+        assert_eq!(Instruction::try_from_known(0xa4), Ok(Instruction::Select));
+        assert_eq!(Instruction::try_from_known(0x12), Err(0x12));
+        for ins in 0..=u8::MAX {
+            match Instruction::from(ins) {
+                Instruction::Unknown(_) => assert_eq!(Instruction::try_from_known(ins), Err(ins)),
+                instruction => assert_eq!(Instruction::try_from_known(ins), Ok(instruction)),
+            }
+        }
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Instruction::Select.to_string(), "SELECT (A4)");
+        assert_eq!(Instruction::GetData.to_string(), "GET DATA (CB)");
+        assert_eq!(Instruction::GetDataEven.to_string(), "GET DATA (CA)");
+        assert_eq!(
+            Instruction::PerformSecurityOperation.to_string(),
+            "PERFORM SECURITY OPERATION (2A)"
+        );
+        assert_eq!(Instruction::Unknown(0x12).to_string(), "INS(0x12)");
+    }
+}
@@ -0,0 +1,160 @@
+//! Parameters of the security related commands (ISO 7816-4 7.5, ISO 7816-8)
+
+/// Control reference template, the component of a security environment set by MANAGE
+/// SECURITY ENVIRONMENT
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum CrtTag {
+    /// Authentication template (AT, `A4`)
+    Authentication = 0xA4,
+    /// Key agreement template (KAT, `A6`)
+    KeyAgreement = 0xA6,
+    /// Hash-code template (HT, `AA`)
+    HashCode = 0xAA,
+    /// Cryptographic checksum template (CCT, `B4`)
+    CryptographicChecksum = 0xB4,
+    /// Digital signature template (DST, `B6`)
+    DigitalSignature = 0xB6,
+    /// Confidentiality template (CT, `B8`)
+    Confidentiality = 0xB8,
+}
+
+impl From<CrtTag> for u8 {
+    fn from(tag: CrtTag) -> u8 {
+        tag as u8
+    }
+}
+
+/// Usage qualifier of MANAGE SECURITY ENVIRONMENT SET, bits 8 to 5 of P1
+///
+/// Qualifiers can be combined with `|`, e.g. `MseUsage::COMPUTATION | MseUsage::VERIFICATION`.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct MseUsage(u8);
+
+impl MseUsage {
+    /// Secure messaging in the command data field
+    pub const SECURE_MESSAGING_COMMAND: Self = Self(0x10);
+    /// Secure messaging in the response data field
+    pub const SECURE_MESSAGING_RESPONSE: Self = Self(0x20);
+    /// Computation, decipherment, internal authentication and key agreement
+    pub const COMPUTATION: Self = Self(0x40);
+    /// Verification, encipherment, external authentication and key agreement
+    pub const VERIFICATION: Self = Self(0x80);
+
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+}
+
+impl core::ops::BitOr for MseUsage {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+/// Operation of a MANAGE SECURITY ENVIRONMENT command, encoded in P1 and P2
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum MseOperation {
+    /// Set the components of a control reference template of the current security
+    /// environment, given as data objects in the command data (P1 `X1`, P2 the CRT tag)
+    Set(MseUsage, CrtTag),
+    /// Store the current security environment under the given number (P1 `F2`)
+    Store(u8),
+    /// Replace the current security environment by the one with the given number (P1 `F3`)
+    Restore(u8),
+    /// Erase the security environment with the given number (P1 `F4`)
+    Erase(u8),
+}
+
+impl MseOperation {
+    pub const fn p1(self) -> u8 {
+        match self {
+            Self::Set(usage, _) => usage.0 | 0x01,
+            Self::Store(_) => 0xF2,
+            Self::Restore(_) => 0xF3,
+            Self::Erase(_) => 0xF4,
+        }
+    }
+
+    pub const fn p2(self) -> u8 {
+        match self {
+            Self::Set(_, tag) => tag as u8,
+            Self::Store(number) | Self::Restore(number) | Self::Erase(number) => number,
+        }
+    }
+}
+
+/// Operation of a PERFORM SECURITY OPERATION command, encoded in P1-P2 (ISO 7816-8)
+///
+/// P1 indicates the data returned in the response, P2 the data given in the command.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum PsoOperation {
+    /// Compute a cryptographic checksum of the data (`8E 80`)
+    ComputeCryptographicChecksum,
+    /// Compute a digital signature of the data, usually a hash or a DigestInfo (`9E 9A`)
+    ComputeDigitalSignature,
+    /// Hash the data (`90 80`)
+    Hash,
+    /// Verify a cryptographic checksum given as data objects (`00 A2`)
+    VerifyCryptographicChecksum,
+    /// Verify a digital signature given as data objects (`00 A8`)
+    VerifyDigitalSignature,
+    /// Verify a certificate (`00 92`)
+    VerifyCertificate,
+    /// Encipher the data, returning a padding indicator followed by the cryptogram (`86 80`)
+    Encipher,
+    /// Decipher a padding indicator followed by the cryptogram, returning the plain value
+    /// (`80 86`)
+    Decipher,
+}
+
+impl PsoOperation {
+    /// P1 and P2 as one big endian value
+    pub const fn p12(self) -> u16 {
+        match self {
+            Self::ComputeCryptographicChecksum => 0x8E80,
+            Self::ComputeDigitalSignature => 0x9E9A,
+            Self::Hash => 0x9080,
+            Self::VerifyCryptographicChecksum => 0x00A2,
+            Self::VerifyDigitalSignature => 0x00A8,
+            Self::VerifyCertificate => 0x0092,
+            Self::Encipher => 0x8680,
+            Self::Decipher => 0x8086,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn mse_operation() {
+        let set = MseOperation::Set(MseUsage::COMPUTATION, CrtTag::DigitalSignature);
+        assert_eq!((set.p1(), set.p2()), (0x41, 0xB6));
+        let set = MseOperation::Set(
+            MseUsage::COMPUTATION | MseUsage::VERIFICATION,
+            CrtTag::Authentication,
+        );
+        assert_eq!((set.p1(), set.p2()), (0xC1, 0xA4));
+        let set = MseOperation::Set(
+            MseUsage::SECURE_MESSAGING_COMMAND | MseUsage::SECURE_MESSAGING_RESPONSE,
+            CrtTag::CryptographicChecksum,
+        );
+        assert_eq!((set.p1(), set.p2()), (0x31, 0xB4));
+
+        assert_eq!(MseOperation::Store(1).p1(), 0xF2);
+        assert_eq!(MseOperation::Restore(3).p1(), 0xF3);
+        assert_eq!(MseOperation::Restore(3).p2(), 0x03);
+        assert_eq!(MseOperation::Erase(2).p1(), 0xF4);
+        assert_eq!(u8::from(CrtTag::Confidentiality), 0xB8);
+    }
+
+    #[test]
+    fn pso_operation() {
+        assert_eq!(PsoOperation::ComputeDigitalSignature.p12(), 0x9E9A);
+        assert_eq!(PsoOperation::Decipher.p12().to_be_bytes(), [0x80, 0x86]);
+        assert_eq!(PsoOperation::Encipher.p12().to_be_bytes(), [0x86, 0x80]);
+    }
+}
@@ -1,5 +1,10 @@
 #![cfg_attr(not(test), no_std)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
 #[macro_use]
 extern crate delog;
 // generate_macros!();
@@ -10,13 +15,54 @@ pub enum Interface {
     Contactless,
 }
 
+/// Data of a [`Command`] or [`Response`], stored inline with a capacity of `S` bytes
+///
+/// `S` is a const generic, e.g. `Data<256>`; this is a [`heapless::Vec`], without allocation.
 pub type Data<const S: usize> = heapless::Vec<u8, S>;
 pub type Result<T = ()> = core::result::Result<T, Status>;
 
+/// Wipe the full backing array of `data`, not only its current contents, and clear it
+#[cfg(feature = "zeroize")]
+fn zeroize_data<const S: usize>(data: &mut Data<S>) {
+    use zeroize::Zeroize;
+
+    data.resize_default(S).ok();
+    data[..].zeroize();
+    data.clear();
+}
+
+/// Assert at compile time that a [`Command`] with a capacity of `S` bytes can hold `len` bytes
+/// of data
+///
+/// ```
+/// const MAX_CERTIFICATE_LEN: usize = 3072;
+/// iso7816::assert_command_capacity!(4096, MAX_CERTIFICATE_LEN);
+/// ```
+///
+/// ```compile_fail
+/// iso7816::assert_command_capacity!(255, 256);
+/// ```
+#[macro_export]
+macro_rules! assert_command_capacity {
+    ($capacity:expr, $len:expr) => {
+        const _: () = ::core::assert!(
+            $crate::Command::<{ $capacity }>::fits($len),
+            "command capacity too small"
+        );
+    };
+}
+
 pub mod aid;
+pub mod capabilities;
 pub mod command;
 pub mod response;
+#[cfg(feature = "tlv")]
+pub mod tlv;
+pub mod transmit;
+pub mod writer;
 
 pub use aid::{Aid, App};
-pub use command::{Command, Instruction};
-pub use response::{Response, Status};
+pub use command::{Command, CommandBuilder, Instruction, Le};
+pub use response::{Response, ResponseBuilder, ResponseView, Status};
+pub use transmit::Transmit;
+pub use writer::Writer;
@@ -1,6 +1,11 @@
+mod reader;
 mod status;
-pub use status::{Status, StatusKind, TriggeringError, WarningCounterError};
+pub use reader::{ReadError, ReadState, ResponseReader};
+pub use status::{
+    Severity, Status, StatusCategory, StatusKind, TriggeringError, WarningCounterError,
+};
 
+use crate::writer::{BufferFull, HexWriter, Writer};
 use crate::Data;
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -14,3 +19,577 @@ impl<const S: usize> Default for Response<S> {
         Self::Status(Default::default())
     }
 }
+
+impl<const S: usize> Response<S> {
+    /// The status of the response: [`Status::SUCCESS`] for `Response::Data`
+    pub fn status(&self) -> Status {
+        match self {
+            Self::Data(_) => Status::SUCCESS,
+            Self::Status(status) => *status,
+        }
+    }
+
+    /// The response data, empty for `Response::Status`
+    pub fn data(&self) -> &[u8] {
+        match self {
+            Self::Data(data) => data,
+            Self::Status(_) => &[],
+        }
+    }
+
+    /// Serialize the response into `writer` as `data || SW1 SW2`, returning the number of bytes
+    /// written
+    ///
+    /// Only the data is stored in the response, the status bytes are appended on the fly.
+    pub fn serialize_into<W: Writer>(&self, writer: &mut W) -> Result<usize, W::Error> {
+        ResponseBuilder::new(self.data(), self.status()).serialize_into(writer)
+    }
+
+    #[cfg(feature = "alloc")]
+    pub fn serialize_to_vec(&self) -> alloc::vec::Vec<u8> {
+        ResponseBuilder::new(self.data(), self.status()).serialize_to_vec()
+    }
+
+    /// Write the response APDU `data || SW1 SW2` into `writer` as space-separated uppercase
+    /// hex, e.g. `01 02 90 00`
+    ///
+    /// See [`CommandView::write_hex`](crate::command::CommandView::write_hex).
+    pub fn write_hex<W: Writer>(&self, writer: &mut W) -> Result<(), W::Error> {
+        self.serialize_into(&mut HexWriter::new(writer)).map(drop)
+    }
+
+    /// Returns `true` if the status is `9000` but the data is shorter than `requested_le`
+    ///
+    /// `requested_le` follows the convention of [`Command::expected`](crate::Command::expected).
+    /// Le is only a maximum, so a short response is valid as far as ISO 7816-4 is concerned:
+    /// this is meant for commands that expect an exact length. A `61XX` response is not
+    /// truncated, as more data is pending.
+    pub fn is_truncated(&self, requested_le: usize) -> bool {
+        is_truncated(self.data(), self.status(), requested_le)
+    }
+
+    /// Split the response into chunks of at most `max_len` data bytes, see
+    /// [`ResponseBuilder::chunks`]
+    pub fn chunks(&self, max_len: usize) -> Chunks<'_> {
+        ResponseBuilder::new(self.data(), self.status()).into_chunks(max_len)
+    }
+
+    /// Append a response chunk, for example the result of a GET RESPONSE issued after a `61XX` status
+    ///
+    /// Returns `true` if the status indicates that more data is available. If `status` is neither
+    /// a success nor a `61XX` status, the response is replaced by `Response::Status(status)`.
+    /// A `Response::Status` is treated as an empty response when new data is appended.
+    ///
+    /// As a `Response` cannot hold data together with a status other than `9000`, this
+    /// discards `data` and the data accumulated so far, even for warnings such as `6282`. Use
+    /// [`ResponseReader`] or [`transmit_full`](crate::transmit::transmit_full) to keep it.
+    pub fn extend_from(&mut self, data: &[u8], status: Status) -> Result<bool, BufferFull> {
+        if !status.is_success() {
+            *self = Self::Status(status);
+            return Ok(false);
+        }
+        if let Self::Status(_) = self {
+            *self = Self::Data(Data::new());
+        }
+        if let Self::Data(buffer) = self {
+            buffer.extend_from_slice(data).map_err(|_| BufferFull)?;
+        }
+        Ok(status.is_more_available())
+    }
+}
+
+/// Wipes the data, including the unused part of the buffer
+#[cfg(feature = "zeroize")]
+impl<const S: usize> zeroize::Zeroize for Response<S> {
+    fn zeroize(&mut self) {
+        if let Self::Data(data) = self {
+            crate::zeroize_data(data);
+        }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<const S: usize> Drop for Response<S> {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<const S: usize> zeroize::ZeroizeOnDrop for Response<S> {}
+
+/// Error returned when parsing a response APDU
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum FromSliceError {
+    /// The APDU is shorter than the two status bytes
+    TooShort,
+    /// The APDU is valid, but its data does not fit into the capacity of the [`Response`]
+    BufferOverflow { needed: usize, capacity: usize },
+}
+
+impl core::fmt::Display for FromSliceError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::TooShort => f.write_str("response APDU shorter than its status"),
+            Self::BufferOverflow { needed, capacity } => write!(
+                f,
+                "response data of {needed} bytes exceeds the capacity of {capacity} bytes"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for FromSliceError {}
+
+/// Parse a response APDU, `data || SW1 SW2`, copying the data
+///
+/// A `9000` response is parsed as `Response::Data`, all others as `Response::Status`. As for
+/// [`Response::extend_from`], the data sent with another status is discarded: use
+/// [`ResponseView`] to access it.
+impl<const S: usize> TryFrom<&[u8]> for Response<S> {
+    type Error = FromSliceError;
+
+    fn try_from(apdu: &[u8]) -> Result<Self, Self::Error> {
+        let view = ResponseView::try_from(apdu)?;
+        match view.status {
+            Status::SUCCESS => Data::from_slice(view.data).map(Self::Data).map_err(|_| {
+                FromSliceError::BufferOverflow {
+                    needed: view.data.len(),
+                    capacity: S,
+                }
+            }),
+            status => Ok(Self::Status(status)),
+        }
+    }
+}
+
+/// Parsed response APDU, `data || SW1 SW2`, borrowing the data
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ResponseView<'a> {
+    data: &'a [u8],
+    status: Status,
+}
+
+impl<'a> ResponseView<'a> {
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    /// Returns `false` if the status is `61XX`, i.e. more data can be requested with GET RESPONSE
+    ///
+    /// A `9000` response is complete, with or without data.
+    pub fn is_complete(&self) -> bool {
+        !self.status.is_more_available()
+    }
+
+    /// Returns `true` if the status is `9000` but the data is shorter than `requested_le`
+    ///
+    /// See [`Response::is_truncated`].
+    pub fn is_truncated(&self, requested_le: usize) -> bool {
+        is_truncated(self.data, self.status, requested_le)
+    }
+
+    /// The channel number in a successful response to MANAGE CHANNEL open
+    ///
+    /// See [`CommandBuilder::manage_channel_open`](crate::CommandBuilder::manage_channel_open).
+    pub fn opened_channel(&self) -> Option<u8> {
+        match (self.status, self.data) {
+            (Status::SUCCESS, &[channel @ 1..=19]) => Some(channel),
+            _ => None,
+        }
+    }
+}
+
+fn is_truncated(data: &[u8], status: Status, requested_le: usize) -> bool {
+    status == Status::SUCCESS && data.len() < requested_le
+}
+
+impl<'a> TryFrom<&'a [u8]> for ResponseView<'a> {
+    type Error = FromSliceError;
+
+    fn try_from(apdu: &'a [u8]) -> Result<Self, Self::Error> {
+        let Some(split) = apdu.len().checked_sub(2) else {
+            return Err(FromSliceError::TooShort);
+        };
+        let (data, status) = apdu.split_at(split);
+        Ok(Self {
+            data,
+            status: Status::from_bytes([status[0], status[1]]),
+        })
+    }
+}
+
+/// Builder for response APDUs, serialized as `data || SW1 SW2`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ResponseBuilder<D> {
+    data: D,
+    status: Status,
+}
+
+impl<D: AsRef<[u8]>> ResponseBuilder<D> {
+    /// Create a response. Use an empty `data` for a response consisting only of the status bytes
+    pub fn new(data: D, status: Status) -> Self {
+        Self { data, status }
+    }
+
+    pub fn data(&self) -> &[u8] {
+        self.data.as_ref()
+    }
+
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    /// Serialize the response into `writer`, returning the number of bytes written
+    pub fn serialize_into<W: Writer>(&self, writer: &mut W) -> Result<usize, W::Error> {
+        writer.write_all(self.data())?;
+        writer.write_all(&self.status.as_bytes())?;
+        Ok(self.data().len() + 2)
+    }
+
+    /// Split the response into chunks of at most `max_len` data bytes, for a card that can only
+    /// send `max_len` bytes per response (usually 256)
+    ///
+    /// All chunks but the last have the status `61XX`, where `XX` is the number of remaining
+    /// bytes, capped at `0xFF`. The last chunk has the status of the response. The remaining
+    /// chunks are usually sent in reply to GET RESPONSE commands.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_len` is 0.
+    pub fn chunks(&self, max_len: usize) -> Chunks<'_> {
+        ResponseBuilder::new(self.data(), self.status).into_chunks(max_len)
+    }
+
+    #[cfg(feature = "alloc")]
+    pub fn serialize_to_vec(&self) -> alloc::vec::Vec<u8> {
+        let mut buffer = alloc::vec::Vec::with_capacity(self.data().len() + 2);
+        self.serialize_into(&mut buffer)
+            .expect("writing to a Vec never fails");
+        buffer
+    }
+}
+
+impl<'a> ResponseBuilder<&'a [u8]> {
+    fn into_chunks(self, max_len: usize) -> Chunks<'a> {
+        assert!(max_len > 0, "chunk length must be positive");
+        Chunks {
+            remaining: Some(self),
+            max_len,
+        }
+    }
+}
+
+/// Iterator over the chunks of a response, see [`ResponseBuilder::chunks`]
+#[derive(Clone, Debug)]
+pub struct Chunks<'a> {
+    remaining: Option<ResponseBuilder<&'a [u8]>>,
+    max_len: usize,
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = ResponseBuilder<&'a [u8]>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let response = self.remaining.take()?;
+        if response.data.len() <= self.max_len {
+            return Some(response);
+        }
+        let (chunk, rest) = response.data.split_at(self.max_len);
+        let available = rest.len().min(0xFF) as u16;
+        self.remaining = Some(ResponseBuilder::new(rest, response.status));
+        Some(ResponseBuilder::new(
+            chunk,
+            Status::more_available(available),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::writer::SliceWriter;
+    use hex_literal::hex;
+
+    #[test]
+    fn response_builder() {
+        let mut buffer = [0; 16];
+        let mut writer = SliceWriter::new(&mut buffer);
+        let response = ResponseBuilder::new(&hex!("0102 03"), Status::SUCCESS);
+        assert_eq!(response.serialize_into(&mut writer), Ok(5));
+        assert_eq!(writer.as_slice(), hex!("010203 9000"));
+
+        let mut writer = SliceWriter::new(&mut buffer);
+        let response = ResponseBuilder::new(&[], Status::FILE_OR_APP_NOT_FOUND);
+        response.serialize_into(&mut writer).unwrap();
+        assert_eq!(writer.as_slice(), hex!("6A82"));
+
+        let mut buffer = [0; 4];
+        let mut writer = SliceWriter::new(&mut buffer);
+        let response = ResponseBuilder::new(&hex!("0102 03"), Status::SUCCESS);
+        assert_eq!(response.serialize_into(&mut writer), Err(BufferFull));
+    }
+
+    #[test]
+    fn response_view() {
+        let view = ResponseView::try_from(&hex!("010203 9000")[..]).unwrap();
+        assert_eq!(view.data(), hex!("010203"));
+        assert_eq!(view.status(), Status::SUCCESS);
+
+        let view = ResponseView::try_from(&hex!("6A82")[..]).unwrap();
+        assert_eq!(view.data(), &[]);
+        assert_eq!(view.status(), Status::FILE_OR_APP_NOT_FOUND);
+
+        assert_eq!(
+            ResponseView::try_from(&hex!("90")[..]),
+            Err(FromSliceError::TooShort)
+        );
+        assert_eq!(
+            ResponseView::try_from(&[][..]),
+            Err(FromSliceError::TooShort)
+        );
+    }
+
+    #[test]
+    fn response_try_from() {
+        assert_eq!(
+            Response::<4>::try_from(&hex!("010203 9000")[..]),
+            Ok(Response::Data(Data::from_slice(&hex!("010203")).unwrap()))
+        );
+        assert_eq!(
+            Response::<4>::try_from(&hex!("9000")[..]),
+            Ok(Response::Data(Data::new()))
+        );
+        assert_eq!(
+            Response::<4>::try_from(&hex!("6A82")[..]),
+            Ok(Response::Status(Status::FILE_OR_APP_NOT_FOUND))
+        );
+        assert_eq!(
+            Response::<4>::try_from(&hex!("0102 6282")[..]),
+            Ok(Response::Status(Status::UNEXPECTED_EOF))
+        );
+        assert_eq!(
+            Response::<2>::try_from(&hex!("010203 9000")[..]),
+            Err(FromSliceError::BufferOverflow {
+                needed: 3,
+                capacity: 2
+            })
+        );
+        assert_eq!(
+            Response::<4>::try_from(&hex!("90")[..]),
+            Err(FromSliceError::TooShort)
+        );
+    }
+
+    #[test]
+    fn error_display() {
+        assert_eq!(
+            FromSliceError::BufferOverflow {
+                needed: 5,
+                capacity: 4
+            }
+            .to_string(),
+            "response data of 5 bytes exceeds the capacity of 4 bytes"
+        );
+        assert_eq!(
+            ReadError::TooShort.to_string(),
+            "response APDU shorter than its status"
+        );
+    }
+
+    #[test]
+    fn is_complete() {
+        let complete = |apdu: &[u8]| ResponseView::try_from(apdu).unwrap().is_complete();
+        assert!(complete(&hex!("010203 9000")));
+        assert!(complete(&hex!("9000")));
+        assert!(!complete(&hex!("010203 6110")));
+        assert!(complete(&hex!("6A82")));
+    }
+
+    #[test]
+    fn opened_channel() {
+        let channel = |apdu: &[u8]| ResponseView::try_from(apdu).unwrap().opened_channel();
+        assert_eq!(channel(&hex!("01 9000")), Some(1));
+        assert_eq!(channel(&hex!("13 9000")), Some(19));
+        assert_eq!(channel(&hex!("00 9000")), None);
+        assert_eq!(channel(&hex!("0102 9000")), None);
+        assert_eq!(channel(&hex!("6881")), None);
+    }
+
+    #[test]
+    fn accessors() {
+        let response = Response::<4>::Data(Data::from_slice(&hex!("0102")).unwrap());
+        assert_eq!(response.data(), hex!("0102"));
+        assert_eq!(response.status(), Status::SUCCESS);
+        let response = Response::<4>::Status(Status::ERROR);
+        assert_eq!(response.data(), &[]);
+        assert_eq!(response.status(), Status::ERROR);
+    }
+
+    #[test]
+    fn extend_from() {
+        let mut response = Response::<8>::default();
+        assert_eq!(
+            response.extend_from(&hex!("0102"), Status::more_available(3)),
+            Ok(true)
+        );
+        assert_eq!(
+            response.extend_from(&hex!("030405"), Status::SUCCESS),
+            Ok(false)
+        );
+        assert_eq!(
+            response,
+            Response::Data(Data::from_slice(&hex!("0102030405")).unwrap())
+        );
+
+        assert_eq!(
+            response.extend_from(&hex!("06070809"), Status::SUCCESS),
+            Err(BufferFull)
+        );
+
+        assert_eq!(response.extend_from(&[], Status::ERROR), Ok(false));
+        assert_eq!(response, Response::Status(Status::ERROR));
+
+        // the data is lost on warnings
+        let mut response = Response::<8>::default();
+        assert_eq!(
+            response.extend_from(&hex!("0102"), Status::more_available(2)),
+            Ok(true)
+        );
+        assert_eq!(
+            response.extend_from(&hex!("0304"), Status::UNEXPECTED_EOF),
+            Ok(false)
+        );
+        assert_eq!(response, Response::Status(Status::UNEXPECTED_EOF));
+        let mut response = Response::<8>::default();
+        assert_eq!(
+            response.extend_from(&hex!("0102"), Status::UNEXPECTED_EOF),
+            Ok(false)
+        );
+        assert_eq!(response.data(), &[]);
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn zeroize() {
+        use zeroize::Zeroize;
+
+        let mut response = Response::<4>::default();
+        response
+            .extend_from(&hex!("01020304"), Status::SUCCESS)
+            .unwrap();
+        response.extend_from(&[], Status::SUCCESS).unwrap();
+        response.zeroize();
+        let Response::Data(data) = &mut response else {
+            panic!("zeroize keeps the variant");
+        };
+        assert!(data.is_empty());
+        data.resize_default(4).unwrap();
+        assert_eq!(data, &[0; 4]);
+    }
+
+    #[test]
+    fn serialize_response() {
+        let mut buffer = [0; 8];
+        let mut writer = SliceWriter::new(&mut buffer);
+        let response = Response::<4>::Data(Data::from_slice(&hex!("0102")).unwrap());
+        assert_eq!(response.serialize_into(&mut writer), Ok(4));
+        assert_eq!(writer.as_slice(), hex!("0102 9000"));
+
+        let mut writer = SliceWriter::new(&mut buffer);
+        let response = Response::<4>::Status(Status::FILE_OR_APP_NOT_FOUND);
+        assert_eq!(response.serialize_into(&mut writer), Ok(2));
+        assert_eq!(writer.as_slice(), hex!("6A82"));
+    }
+
+    #[test]
+    fn write_hex() {
+        let mut buffer = heapless::Vec::<u8, 16>::new();
+        let response = Response::<4>::Data(Data::from_slice(&hex!("01AB")).unwrap());
+        response.write_hex(&mut buffer).unwrap();
+        assert_eq!(buffer, b"01 AB 90 00");
+
+        let mut buffer = heapless::Vec::<u8, 16>::new();
+        Response::<4>::Status(Status::FILE_OR_APP_NOT_FOUND)
+            .write_hex(&mut buffer)
+            .unwrap();
+        assert_eq!(buffer, b"6A 82");
+    }
+
+    #[test]
+    fn is_truncated() {
+        let response = Response::<4>::Data(Data::from_slice(&hex!("0102")).unwrap());
+        assert!(response.is_truncated(3));
+        assert!(!response.is_truncated(2));
+        assert!(!response.is_truncated(0));
+
+        let view = ResponseView::try_from(&hex!("0102 9000")[..]).unwrap();
+        assert!(view.is_truncated(256));
+        assert!(!view.is_truncated(2));
+        let view = ResponseView::try_from(&hex!("0102 6110")[..]).unwrap();
+        assert!(!view.is_truncated(256));
+        let view = ResponseView::try_from(&hex!("6282")[..]).unwrap();
+        assert!(!view.is_truncated(256));
+        assert!(!Response::<4>::Status(Status::ERROR).is_truncated(256));
+    }
+
+    #[test]
+    fn chunks() {
+        let data = [0xAB; 600];
+        let response = ResponseBuilder::new(&data[..], Status::warning_counter(2));
+        let chunks: heapless::Vec<_, 4> = response.chunks(256).collect();
+        assert_eq!(
+            chunks,
+            [
+                ResponseBuilder::new(&data[..256], Status::more_available(0xFF)),
+                ResponseBuilder::new(&data[..256], Status::more_available(88)),
+                ResponseBuilder::new(&data[..88], Status::warning_counter(2)),
+            ]
+        );
+
+        // the reader side reassembles the chunks
+        let response = ResponseBuilder::new(&data[..], Status::SUCCESS);
+        let mut reassembled = Response::<600>::default();
+        for chunk in response.chunks(256) {
+            reassembled
+                .extend_from(chunk.data(), chunk.status())
+                .unwrap();
+        }
+        assert_eq!(reassembled.data(), &data[..]);
+
+        let response = Response::<4>::Data(Data::from_slice(&hex!("01020304")).unwrap());
+        let chunks: heapless::Vec<_, 4> = response.chunks(4).collect();
+        assert_eq!(
+            chunks,
+            [ResponseBuilder::new(&hex!("01020304")[..], Status::SUCCESS)]
+        );
+        let chunks: heapless::Vec<_, 4> = response.chunks(3).collect();
+        assert_eq!(
+            chunks,
+            [
+                ResponseBuilder::new(&hex!("010203")[..], Status::more_available(1)),
+                ResponseBuilder::new(&hex!("04")[..], Status::SUCCESS),
+            ]
+        );
+
+        let response = Response::<4>::Status(Status::FILE_OR_APP_NOT_FOUND);
+        let chunks: heapless::Vec<_, 4> = response.chunks(256).collect();
+        assert_eq!(
+            chunks,
+            [ResponseBuilder::new(&[][..], Status::FILE_OR_APP_NOT_FOUND)]
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn serialize_to_vec() {
+        let response = ResponseBuilder::new(&hex!("0102 03"), Status::SUCCESS);
+        assert_eq!(response.serialize_to_vec(), hex!("010203 9000"));
+        let response = Response::<4>::Data(Data::from_slice(&hex!("0102")).unwrap());
+        assert_eq!(response.serialize_to_vec(), hex!("0102 9000"));
+    }
+}
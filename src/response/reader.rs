@@ -0,0 +1,121 @@
+use super::{FromSliceError, ResponseView, Status};
+use crate::Data;
+
+/// State of a [`ResponseReader`] after a response APDU has been pushed
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ReadState {
+    /// The response is complete, with the status of the last response APDU
+    Complete(Status),
+    /// The card has more data: issue a GET RESPONSE with the given Le, `0` meaning 256
+    ///
+    /// See [`CommandBuilder::get_response`](crate::CommandBuilder::get_response).
+    NeedMore(u8),
+}
+
+/// Error returned by [`ResponseReader::push`]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ReadError {
+    /// The response APDU is shorter than the two status bytes
+    TooShort,
+    /// The accumulated data does not fit into the buffer
+    BufferFull,
+}
+
+impl core::fmt::Display for ReadError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::TooShort => f.write_str("response APDU shorter than its status"),
+            Self::BufferFull => f.write_str("response data exceeds the buffer"),
+        }
+    }
+}
+
+impl core::error::Error for ReadError {}
+
+impl From<FromSliceError> for ReadError {
+    fn from(error: FromSliceError) -> Self {
+        match error {
+            FromSliceError::TooShort => Self::TooShort,
+            FromSliceError::BufferOverflow { .. } => Self::BufferFull,
+        }
+    }
+}
+
+/// Accumulates the data of a response sent in several parts with `61XX` status words
+///
+/// ```
+/// # use iso7816::response::{ReadState, ResponseReader};
+/// let mut reader = ResponseReader::<8>::new();
+/// assert_eq!(reader.push(&[0x01, 0x02, 0x61, 0x03]), Ok(ReadState::NeedMore(3)));
+/// // GET RESPONSE with Le = 3
+/// assert_eq!(
+///     reader.push(&[0x03, 0x04, 0x05, 0x90, 0x00]),
+///     Ok(ReadState::Complete(iso7816::Status::SUCCESS))
+/// );
+/// assert_eq!(reader.data(), &[1, 2, 3, 4, 5]);
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ResponseReader<const S: usize> {
+    data: Data<S>,
+}
+
+impl<const S: usize> ResponseReader<S> {
+    pub fn new() -> Self {
+        Self { data: Data::new() }
+    }
+
+    /// Append a response APDU (`data || SW1 SW2`)
+    ///
+    /// If the data does not fit, the reader is left unchanged.
+    pub fn push(&mut self, apdu: &[u8]) -> Result<ReadState, ReadError> {
+        let response = ResponseView::try_from(apdu)?;
+        self.data
+            .extend_from_slice(response.data())
+            .map_err(|_| ReadError::BufferFull)?;
+        let status = response.status();
+        Ok(match status.as_more_available() {
+            Some(le) => ReadState::NeedMore(le),
+            None => ReadState::Complete(status),
+        })
+    }
+
+    /// The data accumulated so far
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn into_data(self) -> Data<S> {
+        self.data
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn reader() {
+        let mut reader = ResponseReader::<6>::new();
+        assert_eq!(reader.push(&hex!("0102 6100")), Ok(ReadState::NeedMore(0)));
+        assert_eq!(reader.push(&hex!("0304 6102")), Ok(ReadState::NeedMore(2)));
+        assert_eq!(
+            reader.push(&hex!("050607 9000")),
+            Err(ReadError::BufferFull)
+        );
+        assert_eq!(reader.data(), hex!("01020304"));
+        assert_eq!(
+            reader.push(&hex!("0506 9000")),
+            Ok(ReadState::Complete(Status::SUCCESS))
+        );
+        assert_eq!(reader.into_data(), hex!("010203040506"));
+
+        let mut reader = ResponseReader::<6>::new();
+        assert_eq!(reader.push(&hex!("90")), Err(ReadError::TooShort));
+        assert_eq!(
+            reader.push(&hex!("6A82")),
+            Ok(ReadState::Complete(Status::FILE_OR_APP_NOT_FOUND))
+        );
+        assert!(reader.data().is_empty());
+    }
+}
@@ -1,6 +1,6 @@
 use core::fmt::{Debug, Display};
 
-#[derive(Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Debug)]
+#[derive(Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Debug, Hash)]
 #[non_exhaustive]
 pub enum StatusKind {
     Success,
@@ -16,10 +16,16 @@ pub enum StatusKind {
     FileControlInfoBadlyFormatted,
     SelectFileInTerminationState,
     NoInputDataFromSensor,
+    RecordDeactivated,
+    /// Any other `62XX` warning
+    NonVolatileUnchangedWarning(u8),
 
     DataChangedWarning,
     FilledByLastWrite,
-    /// Meaning depends on the command
+    /// Counter value `X` of `63CX`, whose meaning depends on the command
+    ///
+    /// After VERIFY or RESET RETRY COUNTER, this is the number of remaining tries. Other
+    /// commands may use it for another counter, e.g. of write cycles.
     WarningCounter(u8),
 
     ExecutionError,
@@ -30,6 +36,9 @@ pub enum StatusKind {
     DataChangedError,
     MemoryFailure,
 
+    /// Security-related issue, SW2 is not defined by ISO 7816-4
+    SecurityRelated(u8),
+
     ClaNotSupported,
     LogicalChannelNotSupported,
     SecureMessagingNotSupported,
@@ -59,20 +68,168 @@ pub enum StatusKind {
     FileAlreadyExists,
     DfNameAlreadyExists,
 
+    /// Wrong parameters P1-P2 (`0x6B00`)
     WrongParameters,
 
+    /// Wrong Le field, SW2 indicates the number of available bytes
     WrongLeField(u8),
     InstructionNotSupportedOrInvalid,
     ClassNotSupported,
     Error,
 }
 
+impl StatusKind {
+    /// Short description of the status, as given by ISO 7816-4
+    ///
+    /// For kinds that carry a value, the value is not included.
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::Success => "Normal processing",
+
+            Self::MoreAvailable(_) => "Bytes still available",
+
+            Self::DataUnchangedWarning => "Warning, state of non-volatile memory unchanged",
+            Self::WarningTriggering(_) => "Warning triggering by the card",
+            Self::CorruptedData => "Part of returned data may be corrupted",
+            Self::UnexpectedEof => "End of file or record reached before reading Ne bytes",
+            Self::SelectFileDeactivated => "Selected file deactivated",
+            Self::FileControlInfoBadlyFormatted => {
+                "File control information not formatted according to 5.3.3"
+            }
+            Self::SelectFileInTerminationState => "Selected file in termination state",
+            Self::NoInputDataFromSensor => "No input data available from a sensor on the card",
+            Self::RecordDeactivated => "At least one of the referenced records is deactivated",
+            Self::NonVolatileUnchangedWarning(_) => {
+                "Warning, state of non-volatile memory unchanged"
+            }
+
+            Self::DataChangedWarning => "Warning, state of non-volatile memory changed",
+            Self::FilledByLastWrite => "File filled up by the last write",
+            Self::WarningCounter(_) => "Counter value",
+
+            Self::ExecutionError => "Execution error, state of non-volatile memory unchanged",
+            Self::ImmediateResponseRequired => "Immediate response required by the card",
+            Self::ErrorTriggering(_) => "Error triggering by the card",
+
+            Self::DataChangedError => "Execution error, state of non-volatile memory changed",
+            Self::MemoryFailure => "Memory failure",
+
+            Self::SecurityRelated(_) => "Security-related issue",
+
+            Self::ClaNotSupported => "Functions in CLA not supported",
+            Self::LogicalChannelNotSupported => "Logical channel not supported",
+            Self::SecureMessagingNotSupported => "Secure messaging not supported",
+            Self::LastCommanndOfChainExpected => "Last command of the chain expected",
+            Self::CommandChainingNotSupported => "Command chaining not supported",
+
+            Self::CommandNotAllowed => "Command not allowed",
+            Self::CommandIncompatibleFileStructure => "Command incompatible with file structure",
+            Self::SecurityStatusNotSatisfied => "Security status not satisfied",
+            Self::AuthenticationMethodBlocked => "Authentication method blocked",
+            Self::ReferenceDataNotUsable => "Reference data not usable",
+            Self::ConditionOfUseNotSatisfied => "Conditions of use not satisfied",
+            Self::CommandNotAllowedNoEf => "Command not allowed (no current EF)",
+            Self::ExectedSecureMessagingDataObjectsMissing => {
+                "Expected secure messaging data objects missing"
+            }
+            Self::IncorrectSecureMessagingDataObjects => "Incorrect secure messaging data objects",
+
+            Self::WrongParametersNoInfo => "Wrong parameters, no information given",
+            Self::IncorrectParameters => "Incorrect parameters in the command data field",
+            Self::FunctionNotSupported => "Function not supported",
+            Self::FileOrAppNotFound => "File or application not found",
+            Self::RecordNotFound => "Record not found",
+            Self::NotEnoughMemoryInFile => "Not enough memory space in the file",
+            Self::NcInconsistentWithTlv => "Nc inconsistent with TLV structure",
+            Self::IncorrectP1p2 => "Incorrect parameters P1-P2",
+            Self::NcInconsistentWithP1p2 => "Nc inconsistent with parameters P1-P2",
+            Self::ReferenceNotFound => "Referenced data or reference data not found",
+            Self::FileAlreadyExists => "File already exists",
+            Self::DfNameAlreadyExists => "DF name already exists",
+
+            Self::WrongParameters => "Wrong parameters P1-P2",
+
+            Self::WrongLeField(_) => "Wrong Le field, bytes available",
+            Self::InstructionNotSupportedOrInvalid => "Instruction code not supported or invalid",
+            Self::ClassNotSupported => "Class not supported",
+            Self::Error => "No precise diagnosis",
+        }
+    }
+}
+
+impl Display for StatusKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let description = self.description();
+        match *self {
+            Self::MoreAvailable(n) => write!(f, "{n} bytes still available"),
+            Self::WrongLeField(n) => write!(f, "Wrong Le field, {n} bytes available"),
+            Self::WarningCounter(n) => write!(f, "{description}: {n}"),
+            Self::WarningTriggering(n)
+            | Self::NonVolatileUnchangedWarning(n)
+            | Self::ErrorTriggering(n)
+            | Self::SecurityRelated(n) => write!(f, "{description} ({n:02x})"),
+            _ => f.write_str(description),
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for StatusKind {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        let description = self.description();
+        match *self {
+            Self::MoreAvailable(n) => defmt::write!(f, "{=u8} bytes still available", n),
+            Self::WrongLeField(n) => defmt::write!(f, "Wrong Le field, {=u8} bytes available", n),
+            Self::WarningCounter(n) => defmt::write!(f, "{=str}: {=u8}", description, n),
+            Self::WarningTriggering(n)
+            | Self::NonVolatileUnchangedWarning(n)
+            | Self::ErrorTriggering(n)
+            | Self::SecurityRelated(n) => defmt::write!(f, "{=str} ({=u8:02x})", description, n),
+            _ => defmt::write!(f, "{=str}", description),
+        }
+    }
+}
+
+/// Category of a status word, as defined in ISO 7816-4 5.1.3
+#[derive(Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Debug, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum StatusCategory {
+    /// Normal processing (`90XX`, `61XX`)
+    Normal,
+    /// Warning processing (`62XX`, `63XX`)
+    Warning,
+    /// Execution error (`64XX` to `66XX`)
+    ExecutionError,
+    /// Checking error (`67XX` to `6FXX`)
+    CheckingError,
+    /// Any other value of SW1, which is not valid according to ISO 7816-4
+    Other,
+}
+
+/// Severity of a status word, ordered from [`Success`](Severity::Success) to [`Error`](Severity::Error)
+///
+/// This allows finding the worst status of a sequence of responses with
+/// `statuses.map(Status::severity).max()`.
+#[derive(Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Debug, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Severity {
+    /// Normal processing (`90XX`, `61XX`)
+    Success,
+    /// Warning processing (`62XX`, `63XX`)
+    Warning,
+    /// Execution and checking errors, and invalid status words
+    Error,
+}
+
 /// Status bytes from a response APDU.
 ///
 /// This structure can represent any status bytes from a response APDU. For convinience, constants are provided for pattern matching.
 ///
+/// Status words that are not recognized by this crate (for example proprietary `6FXX` or `9XYZ` codes)
+/// are kept as is, so converting a `Status` back into a `u16` or `[u8; 2]` never loses information.
+///
 /// The [`kind`](Status::kind) method can be used to obtain an Enum that can be used to make matching more convenient but is not exhaustive.
-#[derive(Copy, Clone, Eq, PartialEq, PartialOrd, Ord)]
+#[derive(Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Hash)]
 #[repr(transparent)]
 pub struct Status(pub u16);
 
@@ -85,12 +242,34 @@ impl Default for Status {
 impl Debug for Status {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self.kind() {
-            Some(k) => write!(f, "{k:?} ({:02x})", self.0),
-            None => write!(f, "Unknown {:02x}", self.0),
+            Some(k) => write!(f, "{k:?} ({:04x})", self.0),
+            None => write!(f, "Unknown ({:04x})", self.0),
+        }
+    }
+}
+
+/// Prints the status word followed by a short description, e.g. `6985: Conditions of use not satisfied`
+impl Display for Status {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.kind() {
+            Some(k) => write!(f, "{:04X}: {k}", self.0),
+            None => write!(f, "{:04X}: Unknown status", self.0),
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Status {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        match self.kind() {
+            Some(k) => defmt::write!(f, "{=u16:04X}: {}", self.0, k),
+            None => defmt::write!(f, "{=u16:04X}: Unknown status", self.0),
         }
     }
 }
 
+impl core::error::Error for Status {}
+
 impl Status {
     /// `0x9000`
     pub const SUCCESS: Self = Self(0x9000);
@@ -115,6 +294,8 @@ impl Status {
     pub const SELECT_FILE_IN_TERMINATION_STATE: Self = Self(0x6285);
     /// `0x6286`
     pub const NO_INPUT_DATA_FROM_SENSOR: Self = Self(0x6286);
+    /// `0x6287`
+    pub const RECORD_DEACTIVATED: Self = Self(0x6287);
 
     /// 0x6400
     pub const EXECUTION_ERROR: Self = Self(0x6400);
@@ -132,6 +313,8 @@ impl Status {
     /// `0x6581`
     pub const MEMORY_FAILURE: Self = Self(0x6581);
 
+    const SECURITY_RELATED_MASK: u16 = 0x6600;
+
     /// `0x6800`
     pub const CLA_NOT_SUPPORTED: Self = Self(0x6800);
     /// `0x6881`
@@ -187,7 +370,10 @@ impl Status {
     /// `0x6A8A`
     pub const DF_NAME_ALREADY_EXISTS: Self = Self(0x6A8A);
 
-    /// `0x6B00`
+    /// `0x6B00`, wrong parameters P1-P2
+    ///
+    /// ISO 7816-4 does not define any meaning for SW2 in the `6BXX` range, so other values
+    /// are not recognized by [`kind`](Status::kind).
     pub const WRONG_PARAMETERS: Self = Self(0x6B00);
 
     const WRONG_LE_FIELD_MASK: u16 = 0x6C00;
@@ -199,18 +385,83 @@ impl Status {
     /// `0x6F00`
     pub const ERROR: Self = Self(0x6F00);
 
+    /// Every named status, followed by one representative value for each parameterized kind
+    ///
+    /// Each entry is recognized by [`kind`](Status::kind), and every [`StatusKind`] variant is
+    /// represented by exactly one entry. New statuses are appended to this list when they are added to the crate.
+    pub const ALL: &'static [Self] = &[
+        Self::SUCCESS,
+        Self::DATA_UNCHANGED_WARNING,
+        Self::CORRUPTED_DATA,
+        Self::UNEXPECTED_EOF,
+        Self::SELECT_FILE_DEACTIVATED,
+        Self::FILE_CONTROL_INFO_BADLY_FORMATTED,
+        Self::SELECT_FILE_IN_TERMINATION_STATE,
+        Self::NO_INPUT_DATA_FROM_SENSOR,
+        Self::RECORD_DEACTIVATED,
+        Self::EXECUTION_ERROR,
+        Self::IMMEDIATE_RESPONSE_REQUIRED,
+        Self::DATA_CHANGED_WARNING,
+        Self::FILLED_BY_LAST_WRITE,
+        Self::DATA_CHANGED_ERROR,
+        Self::MEMORY_FAILURE,
+        Self::CLA_NOT_SUPPORTED,
+        Self::LOGICAL_CHANNEL_NOT_SUPPORTED,
+        Self::SECURE_MESSAGING_NOT_SUPPORTED,
+        Self::LAST_COMMANND_OF_CHAIN_EXPECTED,
+        Self::COMMAND_CHAINING_NOT_SUPPORTED,
+        Self::COMMAND_NOT_ALLOWED,
+        Self::COMMAND_INCOMPATIBLE_FILE_STRUCTURE,
+        Self::SECURITY_STATUS_NOT_SATISFIED,
+        Self::AUTHENTICATION_METHOD_BLOCKED,
+        Self::REFERENCE_DATA_NOT_USABLE,
+        Self::CONDITION_OF_USE_NOT_SATISFIED,
+        Self::COMMAND_NOT_ALLOWED_NO_EF,
+        Self::EXECTED_SECURE_MESSAGING_DATA_OBJECTS_MISSING,
+        Self::INCORRECT_SECURE_MESSAGING_DATA_OBJECTS,
+        Self::WRONG_PARAMETERS_NO_INFO,
+        Self::INCORRECT_PARAMETERS,
+        Self::FUNCTION_NOT_SUPPORTED,
+        Self::FILE_OR_APP_NOT_FOUND,
+        Self::RECORD_NOT_FOUND,
+        Self::NOT_ENOUGH_MEMORY_IN_FILE,
+        Self::NC_INCONSISTENT_WITH_TLV,
+        Self::INCORRECT_P1P2,
+        Self::NC_INCONSISTENT_WITH_P1P2,
+        Self::REFERENCE_NOT_FOUND,
+        Self::FILE_ALREADY_EXISTS,
+        Self::DF_NAME_ALREADY_EXISTS,
+        Self::WRONG_PARAMETERS,
+        Self::INSTRUCTION_NOT_SUPPORTED_OR_INVALID,
+        Self::CLASS_NOT_SUPPORTED,
+        Self::ERROR,
+        Self::more_available(0x10),
+        Self::warning_triggering(0x02),
+        // any other 62XX warning, StatusKind::NonVolatileUnchangedWarning
+        Self(0x6288),
+        Self::warning_counter(3),
+        Self::error_triggering(0x02),
+        Self::security_related(0x00),
+        Self::wrong_le_field(0x10),
+    ];
+
     /// Create a status representing a wrong LE field (`0x6CXX`)
     pub const fn wrong_le_field(available_bytes: u8) -> Self {
         Self(Self::WRONG_LE_FIELD_MASK | available_bytes as u16)
     }
 
+    /// Create a status representing a security-related issue (`0x66XX`)
+    pub const fn security_related(value: u8) -> Self {
+        Self(Self::SECURITY_RELATED_MASK | value as u16)
+    }
+
     /// Create a status indicating that more data is available (`0x61XX`)
     pub const fn more_available(value: u16) -> Self {
         Self(Self::MORE_AVAILABLE_MASK | value)
     }
 
     pub const fn as_more_available(self) -> Option<u8> {
-        if self.0 | Self::MORE_AVAILABLE_MASK == Self::MORE_AVAILABLE_MASK {
+        if self.0 & 0xFF00 == Self::MORE_AVAILABLE_MASK {
             Some((self.0 & 0x00FF) as u8)
         } else {
             None
@@ -220,34 +471,43 @@ impl Status {
         self.as_more_available().is_some()
     }
 
+    const fn sw1(self) -> u8 {
+        (self.0 >> 8) as u8
+    }
+
+    const fn sw2(self) -> u8 {
+        (self.0 & 0x00FF) as u8
+    }
+
+    /// The processing completed normally (`9000` or `61XX`)
+    pub const fn is_success(self) -> bool {
+        self.0 == Self::SUCCESS.0 || self.is_more_available()
+    }
+
+    /// The processing completed with a warning (`62XX` or `63XX`)
     pub const fn is_warning(self) -> bool {
         self.is_warning_without_modification() || self.is_warning_with_modification()
     }
 
     /// The proccessing raised a warning and did not change state
     pub const fn is_warning_without_modification(self) -> bool {
-        self.0 | 0x6200 == 0x6200
+        self.sw1() == 0x62
     }
     /// The proccessing raised a warning and changed state
     pub const fn is_warning_with_modification(self) -> bool {
-        self.0 | 0x6300 == 0x6300
+        self.sw1() == 0x63
     }
 
+    /// The processing was aborted during execution (`64XX` to `66XX`)
     pub const fn is_execution_error(self) -> bool {
-        self.0 | 0x6400 == 0x6400 || self.0 | 0x6500 == 0x6500 || self.0 | 0x6600 == 0x6600
+        matches!(self.sw1(), 0x64..=0x66)
     }
+    /// The processing was aborted during checking (`67XX` to `6FXX`)
     pub const fn is_checking_error(self) -> bool {
-        self.0 | 0x6700 == 0x6700
-            || self.0 | 0x6800 == 0x6800
-            || self.0 | 0x6900 == 0x6900
-            || self.0 | 0x6A00 == 0x6A00
-            || self.0 | 0x6B00 == 0x6B00
-            || self.0 | 0x6C00 == 0x6C00
-            || self.0 | 0x6D00 == 0x6D00
-            || self.0 | 0x6E00 == 0x6E00
-            || self.0 | 0x6F00 == 0x6F00
+        matches!(self.sw1(), 0x67..=0x6F)
     }
 
+    /// The processing was aborted, either during execution or checking
     pub const fn is_error(self) -> bool {
         self.is_execution_error() || self.is_checking_error()
     }
@@ -276,7 +536,7 @@ impl Status {
     /// Value must be `0x02 <= value < 0x81`, otherwise errors
     pub const fn try_warning_triggering(value: u8) -> Result<Self, TriggeringError> {
         if value <= 0x80 && value >= 0x02 {
-            Ok(Self(Self::WARNING_TRIGGERING_LOWER | value as u16))
+            Ok(Self(Self::DATA_UNCHANGED_WARNING.0 | value as u16))
         } else {
             Err(TriggeringError)
         }
@@ -286,9 +546,10 @@ impl Status {
         self.as_warning_counter().is_some()
     }
 
+    /// Returns the raw counter value `X` of a `63CX` status, see [`StatusKind::WarningCounter`]
     pub const fn as_warning_counter(self) -> Option<u8> {
-        if self.0 | Self::WARNING_COUNTER_MASK == Self::WARNING_COUNTER_MASK {
-            Some((self.0 | 0x00F) as u8)
+        if self.0 & 0xFFF0 == Self::WARNING_COUNTER_MASK {
+            Some((self.0 & 0x000F) as u8)
         } else {
             None
         }
@@ -296,7 +557,7 @@ impl Status {
 
     /// Create a warning counter status. (Meaning depends on the command)
     ///
-    /// Value must be `0x00 <= value < 0x0F`, otherwise errors
+    /// Value must be `0x00 <= value <= 0x0F`, otherwise errors
     pub const fn try_warning_counter(value: u8) -> Result<Self, WarningCounterError> {
         if value <= 0xF {
             Ok(Self(Self::WARNING_COUNTER_MASK | value as u16))
@@ -304,11 +565,14 @@ impl Status {
             Err(WarningCounterError)
         }
     }
-    /// Value must be `0x00 <= value < 0x0F`, otherwise panics
+    /// Value must be `0x00 <= value <= 0x0F`, otherwise panics
+    ///
+    /// This is meant for constants. For a similar method returning a result instead, use
+    /// [try_warning_counter](Status::try_warning_counter)
     pub const fn warning_counter(value: u8) -> Self {
         match Self::try_warning_counter(value) {
             Ok(s) => s,
-            Err(_) => panic!("Expected 0x00 <= value < 0x0F"),
+            Err(_) => panic!("Expected 0x00 <= value <= 0x0F"),
         }
     }
     pub const fn is_error_triggering(self) -> bool {
@@ -335,14 +599,17 @@ impl Status {
     /// Value must be `0x02 <= value < 0x81`, otherwise errors
     pub const fn try_error_triggering(value: u8) -> Result<Self, TriggeringError> {
         if value <= 0x80 && value >= 0x02 {
-            Ok(Self(Self::ERROR_TRIGGERING_LOWER | value as u16))
+            Ok(Self(Self::EXECUTION_ERROR.0 | value as u16))
         } else {
             Err(TriggeringError)
         }
     }
 
+    /// For a wrong LE field status (`0x6CXX`), returns the number of available bytes (SW2)
+    ///
+    /// This is the value to use as Le when re-issuing the command.
     pub const fn as_wrong_le_field(self) -> Option<u8> {
-        if self.0 | Self::WRONG_LE_FIELD_MASK == Self::WRONG_LE_FIELD_MASK {
+        if self.0 & 0xFF00 == Self::WRONG_LE_FIELD_MASK {
             Some((self.0 & 0x00FF) as u8)
         } else {
             None
@@ -351,6 +618,66 @@ impl Status {
     pub const fn is_wrong_le_field(self) -> bool {
         self.as_wrong_le_field().is_some()
     }
+
+    pub const fn as_security_related(self) -> Option<u8> {
+        if self.0 & 0xFF00 == Self::SECURITY_RELATED_MASK {
+            Some(self.sw2())
+        } else {
+            None
+        }
+    }
+    pub const fn is_security_related(self) -> bool {
+        self.as_security_related().is_some()
+    }
+
+    /// Bucket the status into one of the categories of ISO 7816-4 5.1.3, based on SW1
+    pub const fn category(self) -> StatusCategory {
+        match self.sw1() {
+            0x90 | 0x61 => StatusCategory::Normal,
+            0x62 | 0x63 => StatusCategory::Warning,
+            0x64..=0x66 => StatusCategory::ExecutionError,
+            0x67..=0x6F => StatusCategory::CheckingError,
+            _ => StatusCategory::Other,
+        }
+    }
+
+    /// Severity of the status, derived from its [`category`](Status::category)
+    pub const fn severity(self) -> Severity {
+        match self.category() {
+            StatusCategory::Normal => Severity::Success,
+            StatusCategory::Warning => Severity::Warning,
+            StatusCategory::ExecutionError
+            | StatusCategory::CheckingError
+            | StatusCategory::Other => Severity::Error,
+        }
+    }
+
+    /// Convert a successful status (see [`is_success`](Status::is_success)) into `Ok` and any other status into `Err`
+    ///
+    /// Note that warnings are treated as errors. The [`core::error::Error`] implementation of
+    /// `Status` does not make this distinction, so this method should be used before propagating a status with `?`.
+    pub const fn into_result(self) -> Result<Self, Self> {
+        if self.is_success() {
+            Ok(self)
+        } else {
+            Err(self)
+        }
+    }
+
+    /// Create a status from a raw status word
+    ///
+    /// This is the `const` equivalent of `From<u16>`, the reverse is [`as_u16`](Status::as_u16).
+    pub const fn from_u16(sw: u16) -> Self {
+        Self(sw)
+    }
+
+    /// Create a status from the SW1 and SW2 bytes
+    ///
+    /// This is the `const` equivalent of `From<[u8; 2]>`, the reverse is [`as_bytes`](Status::as_bytes).
+    pub const fn from_bytes(sw: [u8; 2]) -> Self {
+        Self(u16::from_be_bytes(sw))
+    }
+
     pub const fn as_bytes(self) -> [u8; 2] {
         self.0.to_be_bytes()
     }
@@ -359,6 +686,10 @@ impl Status {
         self.0
     }
 
+    /// Decode the status word into a [`StatusKind`]
+    ///
+    /// Returns `None` if the status word is not recognized. The raw value is still available
+    /// through [`as_u16`](Status::as_u16).
     pub fn kind(self) -> Option<StatusKind> {
         Some(match self {
             Self::SUCCESS => StatusKind::Success,
@@ -370,6 +701,7 @@ impl Status {
             Self::FILE_CONTROL_INFO_BADLY_FORMATTED => StatusKind::FileControlInfoBadlyFormatted,
             Self::SELECT_FILE_IN_TERMINATION_STATE => StatusKind::SelectFileInTerminationState,
             Self::NO_INPUT_DATA_FROM_SENSOR => StatusKind::NoInputDataFromSensor,
+            Self::RECORD_DEACTIVATED => StatusKind::RecordDeactivated,
 
             Self::DATA_CHANGED_WARNING => StatusKind::DataChangedWarning,
             Self::FILLED_BY_LAST_WRITE => StatusKind::FilledByLastWrite,
@@ -431,8 +763,12 @@ impl Status {
                     StatusKind::MoreAvailable(a)
                 } else if let Some(a) = self.as_wrong_le_field() {
                     StatusKind::WrongLeField(a)
+                } else if let Some(c) = self.as_security_related() {
+                    StatusKind::SecurityRelated(c)
                 } else if let Some(c) = self.as_warning_counter() {
                     StatusKind::WarningCounter(c)
+                } else if self.is_warning_without_modification() {
+                    StatusKind::NonVolatileUnchangedWarning(self.sw2())
                 } else {
                     return None;
                 }
@@ -451,25 +787,56 @@ impl Display for TriggeringError {
     }
 }
 
+impl core::error::Error for TriggeringError {}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
-/// Expected 0x02 <= value < 0x81
+/// Expected 0x00 <= value <= 0x0F
 pub struct WarningCounterError;
 
 impl Display for WarningCounterError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        f.write_str("Expected 0x00 <= value < 0xF")
+        f.write_str("Expected 0x00 <= value <= 0x0F")
+    }
+}
+
+impl core::error::Error for WarningCounterError {}
+
+/// Serialized as the raw status word, so that unknown values round-trip
+#[cfg(feature = "serde")]
+impl serde::Serialize for Status {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u16(self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Status {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        u16::deserialize(deserializer).map(Self::from_u16)
+    }
+}
+
+/// Generates `9000`, status words with SW1 in `61` to `6F` and arbitrary (mostly unknown) values
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Status {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(match u.int_in_range(0..=2)? {
+            0 => Self::SUCCESS,
+            1 => Self::from_bytes([u.int_in_range(0x61..=0x6F)?, u.arbitrary()?]),
+            _ => Self::from_u16(u.arbitrary()?),
+        })
     }
 }
 
 impl From<u16> for Status {
     fn from(value: u16) -> Self {
-        Self(value)
+        Self::from_u16(value)
     }
 }
 
 impl From<[u8; 2]> for Status {
     fn from(value: [u8; 2]) -> Self {
-        u16::from_be_bytes(value).into()
+        Self::from_bytes(value)
     }
 }
 
@@ -481,7 +848,7 @@ impl From<(u8, u8)> for Status {
 
 impl From<Status> for u16 {
     fn from(value: Status) -> Self {
-        value.0
+        value.as_u16()
     }
 }
 
@@ -490,3 +857,238 @@ impl From<Status> for [u8; 2] {
         value.as_bytes()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn all() {
+        let mut kinds = std::collections::HashSet::new();
+        for &status in Status::ALL {
+            let kind = status.kind().expect("every entry of ALL is known");
+            assert!(kinds.insert(kind));
+            assert_eq!(Status::from(u16::from(status)), status);
+            assert_eq!(Status::from(<[u8; 2]>::from(status)), status);
+        }
+        assert_eq!(kinds.len(), Status::ALL.len());
+        assert!(Status::ALL.contains(&Status::SUCCESS));
+        assert!(Status::ALL.contains(&Status::ERROR));
+    }
+
+    #[test]
+    fn unknown_round_trip() {
+        for sw in [0x6F12, 0x9123, 0x9FFF, 0x0000, 0xFFFF] {
+            let status = Status::from(sw);
+            assert_eq!(status.kind(), None);
+            assert_eq!(u16::from(status), sw);
+            assert_eq!(Status::from(<[u8; 2]>::from(status)), status);
+        }
+    }
+
+    #[test]
+    fn known_before_unknown() {
+        assert_eq!(Status::from(0x9000).kind(), Some(StatusKind::Success));
+        assert_eq!(Status::from(0x6F00).kind(), Some(StatusKind::Error));
+        assert_eq!(
+            Status::from((0x69, 0x85)).kind(),
+            Some(StatusKind::ConditionOfUseNotSatisfied)
+        );
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            Status::CONDITION_OF_USE_NOT_SATISFIED.to_string(),
+            "6985: Conditions of use not satisfied"
+        );
+        assert_eq!(
+            Status::more_available(0x12).to_string(),
+            "6112: 18 bytes still available"
+        );
+        assert_eq!(
+            Status::warning_counter(3).to_string(),
+            "63C3: Counter value: 3"
+        );
+        assert_eq!(Status::from(0x9123).to_string(), "9123: Unknown status");
+    }
+
+    #[test]
+    fn warnings_unchanged() {
+        assert_eq!(Status::from(0x6281).kind(), Some(StatusKind::CorruptedData));
+        assert_eq!(Status::from(0x6282).kind(), Some(StatusKind::UnexpectedEof));
+        assert_eq!(
+            Status::from(0x6283).kind(),
+            Some(StatusKind::SelectFileDeactivated)
+        );
+        assert_eq!(
+            Status::from(0x6285).kind(),
+            Some(StatusKind::SelectFileInTerminationState)
+        );
+        assert_eq!(
+            Status::from(0x6287).kind(),
+            Some(StatusKind::RecordDeactivated)
+        );
+        assert_eq!(
+            Status::from(0x6210).kind(),
+            Some(StatusKind::WarningTriggering(0x10))
+        );
+        assert_eq!(
+            Status::from(0x62A0).kind(),
+            Some(StatusKind::NonVolatileUnchangedWarning(0xA0))
+        );
+        assert_eq!(Status::warning_triggering(0x04), Status::from(0x6204));
+        assert_eq!(Status::error_triggering(0x04), Status::from(0x6404));
+    }
+
+    #[test]
+    fn wrong_le_field() {
+        for available in [0x00, 0x10, 0xFF] {
+            let status = Status::wrong_le_field(available);
+            assert_eq!(u16::from(status), 0x6C00 | available as u16);
+            assert_eq!(status.as_wrong_le_field(), Some(available));
+            assert_eq!(status.kind(), Some(StatusKind::WrongLeField(available)));
+        }
+        assert_eq!(Status::from(0x6D00).as_wrong_le_field(), None);
+        assert_eq!(Status::from(0x6B00).as_wrong_le_field(), None);
+    }
+
+    #[test]
+    fn wrong_parameters() {
+        assert_eq!(
+            Status::from((0x6B, 0x00)).kind(),
+            Some(StatusKind::WrongParameters)
+        );
+        assert_eq!(u16::from(Status::WRONG_PARAMETERS), 0x6B00);
+        assert!(Status::WRONG_PARAMETERS.is_checking_error());
+    }
+
+    #[test]
+    fn security_related() {
+        for sw2 in [0x00, 0x88, 0xFF] {
+            let status = Status::security_related(sw2);
+            assert_eq!(u16::from(status), 0x6600 | sw2 as u16);
+            assert_eq!(status.kind(), Some(StatusKind::SecurityRelated(sw2)));
+            assert!(status.is_execution_error());
+        }
+        assert_eq!(Status::MEMORY_FAILURE.as_security_related(), None);
+    }
+
+    #[test]
+    fn warning_counter() {
+        for value in 0..=0x0F {
+            let status = Status::try_warning_counter(value).unwrap();
+            assert_eq!(u16::from(status), 0x63C0 | value as u16);
+            assert_eq!(status.as_warning_counter(), Some(value));
+        }
+        assert_eq!(Status::try_warning_counter(0x10), Err(WarningCounterError));
+        assert_eq!(Status::try_warning_counter(0xFF), Err(WarningCounterError));
+        assert_eq!(Status::FILLED_BY_LAST_WRITE.as_warning_counter(), None);
+    }
+
+    #[test]
+    fn into_result() {
+        assert_eq!(Status::SUCCESS.into_result(), Ok(Status::SUCCESS));
+        assert_eq!(
+            Status::more_available(0x20).into_result(),
+            Ok(Status::more_available(0x20))
+        );
+        assert_eq!(
+            Status::CORRUPTED_DATA.into_result(),
+            Err(Status::CORRUPTED_DATA)
+        );
+
+        fn run(status: Status) -> std::result::Result<(), Box<dyn std::error::Error>> {
+            status.into_result()?;
+            Ok(())
+        }
+        assert!(run(Status::SUCCESS).is_ok());
+        assert_eq!(
+            run(Status::ERROR).unwrap_err().to_string(),
+            "6F00: No precise diagnosis"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        use serde_test::{assert_tokens, Token};
+        assert_tokens(&Status::SUCCESS, &[Token::U16(0x9000)]);
+        assert_tokens(&Status::more_available(0x12), &[Token::U16(0x6112)]);
+        assert_tokens(&Status::from(0x9123), &[Token::U16(0x9123)]);
+    }
+
+    #[test]
+    fn severity() {
+        assert!(Severity::Success < Severity::Warning);
+        assert!(Severity::Warning < Severity::Error);
+        assert_eq!(Status::more_available(1).severity(), Severity::Success);
+        assert_eq!(Status::warning_counter(2).severity(), Severity::Warning);
+        assert_eq!(
+            Status::SECURITY_STATUS_NOT_SATISFIED.severity(),
+            Severity::Error
+        );
+        assert_eq!(Status::from(0x9123).severity(), Severity::Error);
+
+        let statuses = [Status::SUCCESS, Status::CORRUPTED_DATA, Status::SUCCESS];
+        assert_eq!(
+            statuses.into_iter().map(Status::severity).max(),
+            Some(Severity::Warning)
+        );
+    }
+
+    #[test]
+    fn category() {
+        assert_eq!(Status::SUCCESS.category(), StatusCategory::Normal);
+        assert_eq!(
+            Status::more_available(0x10).category(),
+            StatusCategory::Normal
+        );
+        assert_eq!(Status::CORRUPTED_DATA.category(), StatusCategory::Warning);
+        assert_eq!(
+            Status::warning_counter(1).category(),
+            StatusCategory::Warning
+        );
+        assert_eq!(
+            Status::MEMORY_FAILURE.category(),
+            StatusCategory::ExecutionError
+        );
+        assert_eq!(
+            Status::security_related(0).category(),
+            StatusCategory::ExecutionError
+        );
+        assert_eq!(
+            Status::from(0x6700).category(),
+            StatusCategory::CheckingError
+        );
+        assert_eq!(Status::ERROR.category(), StatusCategory::CheckingError);
+        assert_eq!(Status::from(0x9123).category(), StatusCategory::Other);
+    }
+
+    #[test]
+    fn const_conversions() {
+        const EXPECTED: [Status; 2] = [Status::from_u16(0x9000), Status::from_bytes([0x61, 0x00])];
+        assert_eq!(EXPECTED, [Status::SUCCESS, Status::more_available(0)]);
+        assert_eq!(Status::from_u16(0x6A82).as_u16(), 0x6A82);
+        assert_eq!(Status::from_bytes([0x6A, 0x82]).as_bytes(), [0x6A, 0x82]);
+    }
+
+    #[test]
+    fn classification() {
+        const { assert!(Status::SUCCESS.is_success()) };
+        assert!(Status::more_available(0x10).is_success());
+        assert!(!Status::DATA_CHANGED_WARNING.is_success());
+
+        assert!(Status::CORRUPTED_DATA.is_warning());
+        assert!(Status::warning_counter(2).is_warning());
+        assert!(!Status::SUCCESS.is_warning());
+        assert!(!Status::from(0x0062).is_warning());
+
+        assert!(Status::MEMORY_FAILURE.is_execution_error());
+        assert!(Status::from(0x6600).is_execution_error());
+        assert!(Status::SECURITY_STATUS_NOT_SATISFIED.is_checking_error());
+        assert!(Status::ERROR.is_error());
+        assert!(!Status::SUCCESS.is_error());
+        assert!(!Status::CORRUPTED_DATA.is_error());
+    }
+}
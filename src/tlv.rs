@@ -0,0 +1,316 @@
+//! Minimal, allocation-free BER-TLV and SIMPLE-TLV support (ISO 7816-4 5.2)
+//!
+//! This module handles BER-TLV data objects, [`simple`] handles SIMPLE-TLV data objects.
+//! Constructed data objects are not decoded recursively: parse their value again to access
+//! the nested data objects.
+//!
+//! ```
+//! # use iso7816::tlv::{self, Tlv};
+//! let mut buffer = heapless::Vec::<u8, 16>::new();
+//! Tlv::try_new(0x5F50, b"url").unwrap().encode_into(&mut buffer).unwrap();
+//! Tlv::try_new(0x4F, &[0xA0, 0x00]).unwrap().encode_into(&mut buffer).unwrap();
+//! assert_eq!(buffer, [0x5F, 0x50, 0x03, b'u', b'r', b'l', 0x4F, 0x02, 0xA0, 0x00]);
+//!
+//! let mut objects = tlv::parse(&buffer);
+//! assert_eq!(objects.next(), Some(Ok(Tlv::try_new(0x5F50, b"url").unwrap())));
+//! assert_eq!(tlv::find(&buffer, 0x4F), Some(&[0xA0, 0x00][..]));
+//! ```
+
+pub mod simple;
+
+use crate::writer::Writer;
+
+/// Error for malformed TLV data objects
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// The data ends in the middle of a data object
+    Truncated,
+    /// The tag is not valid for the encoding (or longer than four bytes for BER-TLV)
+    InvalidTag,
+    /// The length is not valid for the encoding, e.g. the BER-TLV indefinite form
+    InvalidLength,
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("truncated TLV data object"),
+            Self::InvalidTag => f.write_str("invalid TLV tag"),
+            Self::InvalidLength => f.write_str("invalid TLV length"),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
+
+/// Largest value length that can be encoded, with four length bytes
+const MAX_LEN: usize = u32::MAX as usize;
+
+/// BER-TLV data object
+///
+/// The tag is stored with its encoded bytes in big endian order, e.g. `0x5F50` or `0x7F66`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Tlv<'a> {
+    tag: u32,
+    value: &'a [u8],
+}
+
+impl<'a> Tlv<'a> {
+    /// Create a data object, checking that `tag` is a valid BER-TLV tag
+    ///
+    /// Tags `00` and `FF` are rejected, as they are used for padding.
+    pub fn try_new(tag: u32, value: &'a [u8]) -> Result<Self, Error> {
+        let bytes = tag.to_be_bytes();
+        let tag_len = tag_len(tag);
+        if !is_valid_tag(&bytes[bytes.len() - tag_len..]) {
+            return Err(Error::InvalidTag);
+        }
+        if value.len() > MAX_LEN {
+            return Err(Error::InvalidLength);
+        }
+        Ok(Self { tag, value })
+    }
+
+    pub fn tag(&self) -> u32 {
+        self.tag
+    }
+
+    pub fn value(&self) -> &'a [u8] {
+        self.value
+    }
+
+    /// Returns `true` if the data object is constructed, i.e. its value consists of data
+    /// objects
+    pub fn is_constructed(&self) -> bool {
+        let first = self.tag.to_be_bytes()[4 - tag_len(self.tag)];
+        first & 0x20 != 0
+    }
+
+    /// Iterate over the data objects contained in the value of a constructed data object
+    pub fn children(&self) -> Iter<'a> {
+        parse(self.value)
+    }
+
+    /// Number of bytes written by [`encode_into`](Self::encode_into)
+    pub fn encoded_len(&self) -> usize {
+        tag_len(self.tag) + length_len(self.value.len()) + self.value.len()
+    }
+
+    /// Write the tag, length and value into `writer`
+    pub fn encode_into<W: Writer + ?Sized>(&self, writer: &mut W) -> Result<(), W::Error> {
+        let tag = self.tag.to_be_bytes();
+        writer.write_all(&tag[tag.len() - tag_len(self.tag)..])?;
+
+        let len = self.value.len();
+        let len_bytes = (len as u32).to_be_bytes();
+        match length_len(len) {
+            1 => writer.write_all(&[len as u8])?,
+            n => {
+                writer.write_all(&[0x80 | (n as u8 - 1)])?;
+                writer.write_all(&len_bytes[len_bytes.len() - (n - 1)..])?;
+            }
+        }
+        writer.write_all(self.value)
+    }
+}
+
+/// Number of significant bytes of `tag`, at least one
+fn tag_len(tag: u32) -> usize {
+    (4 - tag.leading_zeros() as usize / 8).max(1)
+}
+
+fn is_valid_tag(bytes: &[u8]) -> bool {
+    match bytes {
+        [] | [0x00] | [0xFF, ..] => false,
+        [first] => first & 0x1F != 0x1F,
+        [first, middle @ .., last] => {
+            first & 0x1F == 0x1F && middle.iter().all(|b| b & 0x80 != 0) && last & 0x80 == 0
+        }
+    }
+}
+
+/// Number of bytes of the length field for a value of `len` bytes
+fn length_len(len: usize) -> usize {
+    match len {
+        0..=0x7F => 1,
+        0x80..=0xFF => 2,
+        0x100..=0xFFFF => 3,
+        0x1_0000..=0xFF_FFFF => 4,
+        _ => 5,
+    }
+}
+
+/// Iterate over the BER-TLV data objects in `data`
+///
+/// `00` and `FF` bytes before, between and after data objects are skipped.
+pub fn parse(data: &[u8]) -> Iter<'_> {
+    Iter { data }
+}
+
+/// Return the value of the first data object with the given tag in `data`, without descending
+/// into constructed data objects
+///
+/// Parsing stops at the first malformed data object.
+pub fn find(data: &[u8], tag: u32) -> Option<&[u8]> {
+    parse(data)
+        .map_while(Result::ok)
+        .find(|tlv| tlv.tag == tag)
+        .map(|tlv| tlv.value)
+}
+
+/// Iterator over BER-TLV data objects, see [`parse`]
+///
+/// After an error, the iterator returns `None`.
+#[derive(Clone, Debug)]
+pub struct Iter<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Iter<'a> {
+    fn parse_next(&mut self) -> Result<Tlv<'a>, Error> {
+        let data = self.data;
+
+        let mut tag_len = 1;
+        if data[0] & 0x1F == 0x1F {
+            loop {
+                let byte = *data.get(tag_len).ok_or(Error::Truncated)?;
+                tag_len += 1;
+                if byte & 0x80 == 0 {
+                    break;
+                }
+                if tag_len == 4 {
+                    return Err(Error::InvalidTag);
+                }
+            }
+        }
+        let tag = data[..tag_len]
+            .iter()
+            .fold(0, |tag, &b| (tag << 8) | u32::from(b));
+
+        let first = *data.get(tag_len).ok_or(Error::Truncated)?;
+        let (len, length_len) = match first {
+            0x00..=0x7F => (usize::from(first), 1),
+            0x81..=0x84 => {
+                let n = usize::from(first & 0x7F);
+                let bytes = data
+                    .get(tag_len + 1..tag_len + 1 + n)
+                    .ok_or(Error::Truncated)?;
+                let len = bytes.iter().fold(0, |len, &b| (len << 8) | usize::from(b));
+                (len, 1 + n)
+            }
+            _ => return Err(Error::InvalidLength),
+        };
+
+        let rest = &data[tag_len + length_len..];
+        if rest.len() < len {
+            return Err(Error::Truncated);
+        }
+        let (value, rest) = rest.split_at(len);
+        self.data = rest;
+        Ok(Tlv { tag, value })
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = Result<Tlv<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let [0x00 | 0xFF, rest @ ..] = self.data {
+            self.data = rest;
+        }
+        if self.data.is_empty() {
+            return None;
+        }
+        let result = self.parse_next();
+        if result.is_err() {
+            self.data = &[];
+        }
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hex_literal::hex;
+
+    fn encode(tag: u32, value: &[u8]) -> heapless::Vec<u8, 512> {
+        let tlv = Tlv::try_new(tag, value).unwrap();
+        let mut buffer = heapless::Vec::new();
+        tlv.encode_into(&mut buffer).unwrap();
+        assert_eq!(buffer.len(), tlv.encoded_len());
+        buffer
+    }
+
+    #[test]
+    fn encode_into() {
+        assert_eq!(encode(0x4F, &hex!("A000")), hex!("4F 02 A000"));
+        assert_eq!(encode(0x5F50, &[]), hex!("5F50 00"));
+        assert_eq!(encode(0x7F66, &hex!("0102")), hex!("7F66 02 0102"));
+        assert_eq!(encode(0x5F8101, &hex!("01")), hex!("5F8101 01 01"));
+        assert_eq!(&encode(0x53, &[0; 0x7F])[..2], hex!("53 7F"));
+        assert_eq!(&encode(0x53, &[0; 0x80])[..3], hex!("53 81 80"));
+        assert_eq!(&encode(0x53, &[0; 0x100])[..4], hex!("53 82 0100"));
+
+        for tag in [
+            0x00, 0xFF, 0x1F, 0x5F, 0x5F80, 0x4F4F, 0xFF01, 0x5F818181, 0x5F808080,
+        ] {
+            assert_eq!(Tlv::try_new(tag, &[]), Err(Error::InvalidTag), "{tag:X}");
+        }
+        assert!(Tlv::try_new(0x5F818101, &[]).is_ok());
+    }
+
+    #[test]
+    fn parse() {
+        let data = hex!("00 4F 02 A000 FFFF 7F66 03 020100 53 81 01 AA 00");
+        let objects: heapless::Vec<_, 4> = super::parse(&data).collect();
+        assert_eq!(
+            objects,
+            [
+                Ok(Tlv::try_new(0x4F, &hex!("A000")).unwrap()),
+                Ok(Tlv::try_new(0x7F66, &hex!("020100")).unwrap()),
+                Ok(Tlv::try_new(0x53, &hex!("AA")).unwrap()),
+            ]
+        );
+        assert!(objects[1].as_ref().unwrap().is_constructed());
+        assert!(!objects[0].as_ref().unwrap().is_constructed());
+        assert_eq!(
+            objects[1].as_ref().unwrap().children().next(),
+            Some(Ok(Tlv::try_new(0x02, &hex!("00")).unwrap()))
+        );
+
+        assert_eq!(find(&data, 0x53), Some(&hex!("AA")[..]));
+        assert_eq!(find(&data, 0x02), None);
+
+        for (data, error) in [
+            (&hex!("4F")[..], Error::Truncated),
+            (&hex!("4F 02 00"), Error::Truncated),
+            (&hex!("5F"), Error::Truncated),
+            (&hex!("5F 81"), Error::Truncated),
+            (&hex!("4F 82 01"), Error::Truncated),
+            (&hex!("4F 80 00 00"), Error::InvalidLength),
+            (&hex!("4F 85 00 00 00 00 01 00"), Error::InvalidLength),
+            (&hex!("5F 81 81 81 01 00"), Error::InvalidTag),
+        ] {
+            let mut iter = super::parse(data);
+            assert_eq!(iter.next(), Some(Err(error)), "{data:02X?}");
+            assert_eq!(iter.next(), None);
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        for tag in [0x01, 0x4F, 0x5F50, 0x7F66, 0x5F8101, 0x5F818101] {
+            for len in [0, 1, 0x7F, 0x80, 0xFF, 0x100, 500] {
+                let value = [0xAB; 500];
+                let encoded = encode(tag, &value[..len]);
+                let mut iter = super::parse(&encoded);
+                assert_eq!(
+                    iter.next(),
+                    Some(Ok(Tlv::try_new(tag, &value[..len]).unwrap()))
+                );
+                assert_eq!(iter.next(), None);
+            }
+        }
+    }
+}
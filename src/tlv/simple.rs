@@ -0,0 +1,177 @@
+//! SIMPLE-TLV data objects (ISO 7816-4 5.2.1)
+//!
+//! The tag is a single byte from `01` to `FE`. The length is a single byte from `00` to `FE`,
+//! or `FF` followed by two bytes for lengths up to 65535.
+
+use super::Error;
+use crate::writer::Writer;
+
+const MAX_LEN: usize = 0xFFFF;
+
+/// SIMPLE-TLV data object
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Tlv<'a> {
+    tag: u8,
+    value: &'a [u8],
+}
+
+impl<'a> Tlv<'a> {
+    /// Create a data object, checking that `tag` is valid and that `value` is at most 65535
+    /// bytes long
+    pub fn try_new(tag: u8, value: &'a [u8]) -> Result<Self, Error> {
+        if matches!(tag, 0x00 | 0xFF) {
+            return Err(Error::InvalidTag);
+        }
+        if value.len() > MAX_LEN {
+            return Err(Error::InvalidLength);
+        }
+        Ok(Self { tag, value })
+    }
+
+    pub fn tag(&self) -> u8 {
+        self.tag
+    }
+
+    pub fn value(&self) -> &'a [u8] {
+        self.value
+    }
+
+    /// Number of bytes written by [`encode_into`](Self::encode_into)
+    pub fn encoded_len(&self) -> usize {
+        let length_len = if self.value.len() < 0xFF { 1 } else { 3 };
+        1 + length_len + self.value.len()
+    }
+
+    /// Write the tag, length and value into `writer`
+    pub fn encode_into<W: Writer + ?Sized>(&self, writer: &mut W) -> Result<(), W::Error> {
+        writer.write_all(&[self.tag])?;
+        let len = self.value.len();
+        if len < 0xFF {
+            writer.write_all(&[len as u8])?;
+        } else {
+            let [high, low] = (len as u16).to_be_bytes();
+            writer.write_all(&[0xFF, high, low])?;
+        }
+        writer.write_all(self.value)
+    }
+}
+
+/// Iterate over the SIMPLE-TLV data objects in `data`
+pub fn parse(data: &[u8]) -> Iter<'_> {
+    Iter { data }
+}
+
+/// Return the value of the first data object with the given tag in `data`
+///
+/// Parsing stops at the first malformed data object.
+pub fn find(data: &[u8], tag: u8) -> Option<&[u8]> {
+    parse(data)
+        .map_while(Result::ok)
+        .find(|tlv| tlv.tag == tag)
+        .map(|tlv| tlv.value)
+}
+
+/// Iterator over SIMPLE-TLV data objects, see [`parse`]
+///
+/// After an error, the iterator returns `None`.
+#[derive(Clone, Debug)]
+pub struct Iter<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Iter<'a> {
+    fn parse_next(&mut self) -> Result<Tlv<'a>, Error> {
+        let (&tag, rest) = self.data.split_first().ok_or(Error::Truncated)?;
+        if matches!(tag, 0x00 | 0xFF) {
+            return Err(Error::InvalidTag);
+        }
+        let (len, rest) = match rest {
+            [0xFF, high, low, rest @ ..] => (usize::from(u16::from_be_bytes([*high, *low])), rest),
+            [0xFF, ..] | [] => return Err(Error::Truncated),
+            [len, rest @ ..] => (usize::from(*len), rest),
+        };
+        if rest.len() < len {
+            return Err(Error::Truncated);
+        }
+        let (value, rest) = rest.split_at(len);
+        self.data = rest;
+        Ok(Tlv { tag, value })
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = Result<Tlv<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let result = self.parse_next();
+        if result.is_err() {
+            self.data = &[];
+        }
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hex_literal::hex;
+
+    fn encode(tag: u8, value: &[u8]) -> heapless::Vec<u8, 512> {
+        let tlv = Tlv::try_new(tag, value).unwrap();
+        let mut buffer = heapless::Vec::new();
+        tlv.encode_into(&mut buffer).unwrap();
+        assert_eq!(buffer.len(), tlv.encoded_len());
+        buffer
+    }
+
+    #[test]
+    fn encode_into() {
+        assert_eq!(encode(0x01, &hex!("AABB")), hex!("01 02 AABB"));
+        assert_eq!(encode(0xFE, &[]), hex!("FE 00"));
+        assert_eq!(&encode(0x10, &[0; 0xFE])[..2], hex!("10 FE"));
+        assert_eq!(&encode(0x10, &[0; 0xFF])[..4], hex!("10 FF 00FF"));
+        assert_eq!(&encode(0x10, &[0; 0x100])[..4], hex!("10 FF 0100"));
+
+        assert_eq!(Tlv::try_new(0x00, &[]), Err(Error::InvalidTag));
+        assert_eq!(Tlv::try_new(0xFF, &[]), Err(Error::InvalidTag));
+        assert_eq!(
+            Tlv::try_new(0x01, &vec![0; 0x1_0000]),
+            Err(Error::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn parse() {
+        let data = hex!("01 02 AABB 02 00 03 FF 0001 CC");
+        let mut iter = super::parse(&data);
+        assert_eq!(
+            iter.next(),
+            Some(Ok(Tlv::try_new(0x01, &hex!("AABB")).unwrap()))
+        );
+        assert_eq!(iter.next(), Some(Ok(Tlv::try_new(0x02, &[]).unwrap())));
+        assert_eq!(
+            iter.next(),
+            Some(Ok(Tlv::try_new(0x03, &hex!("CC")).unwrap()))
+        );
+        assert_eq!(iter.next(), None);
+
+        assert_eq!(find(&data, 0x03), Some(&hex!("CC")[..]));
+        assert_eq!(find(&data, 0x04), None);
+
+        for (data, error) in [
+            (&hex!("01")[..], Error::Truncated),
+            (&hex!("01 02 AA"), Error::Truncated),
+            (&hex!("01 FF 00"), Error::Truncated),
+            (&hex!("01 FF 0002 AA"), Error::Truncated),
+            (&hex!("00 01 AA"), Error::InvalidTag),
+            (&hex!("FF 01 AA"), Error::InvalidTag),
+        ] {
+            let mut iter = super::parse(data);
+            assert_eq!(iter.next(), Some(Err(error)), "{data:02X?}");
+            assert_eq!(iter.next(), None);
+        }
+    }
+}
@@ -0,0 +1,332 @@
+//! Transport-agnostic exchange of commands and responses with a card
+//!
+//! Backends (PC/SC, CCID, NFC, ...) implement [`Transmit`] for the exchange of a single APDU.
+//! [`transmit`] and [`transmit_full`] build on it to send a complete command, using command
+//! chaining and GET RESPONSE as needed.
+
+use crate::command::class::Class;
+use crate::response::{ReadError, ReadState, ResponseReader, ResponseView};
+use crate::{CommandBuilder, Data, Response, Status};
+
+/// Maximum length of the command APDUs sent by [`transmit`] without `supports_extended`: a
+/// short APDU with 255 bytes of data and a short Le field
+const MAX_SHORT_COMMAND_LEN: usize = 4 + 1 + 255 + 1;
+
+/// Maximum length of the command APDUs sent by [`transmit`] with `supports_extended`: an
+/// extended APDU with 65535 bytes of data and an extended Le field
+const MAX_EXTENDED_COMMAND_LEN: usize = 4 + 3 + 65_535 + 2;
+
+/// Exchange of a single APDU with a card
+pub trait Transmit {
+    type Error;
+
+    /// Send the command APDU `command` and return the response APDU, `data || SW1 SW2`
+    fn transmit(&mut self, command: &[u8]) -> Result<&[u8], Self::Error>;
+}
+
+impl<T: Transmit + ?Sized> Transmit for &mut T {
+    type Error = T::Error;
+
+    fn transmit(&mut self, command: &[u8]) -> Result<&[u8], Self::Error> {
+        (**self).transmit(command)
+    }
+}
+
+/// Error returned by [`transmit`]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Error<E> {
+    /// The transport failed
+    Transport(E),
+    /// The card sent a response shorter than the two status bytes, or more data without
+    /// sending any data
+    InvalidResponse,
+    /// The response data does not fit into the capacity of the [`Response`], or the command
+    /// cannot be serialized (see [`CommandBuilder::serialize_into`])
+    BufferFull,
+}
+
+impl<E: core::fmt::Display> core::fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Transport(error) => write!(f, "transport error: {error}"),
+            Self::InvalidResponse => f.write_str("invalid response APDU"),
+            Self::BufferFull => f.write_str("buffer full"),
+        }
+    }
+}
+
+impl<E: core::fmt::Debug + core::fmt::Display> core::error::Error for Error<E> {}
+
+impl<E> From<ReadError> for Error<E> {
+    fn from(error: ReadError) -> Self {
+        match error {
+            ReadError::TooShort => Self::InvalidResponse,
+            ReadError::BufferFull => Self::BufferFull,
+        }
+    }
+}
+
+/// Send `command` and return the complete response
+///
+/// Commands with too much data for a short APDU are sent using command chaining, stopping at
+/// the first intermediate response that is not `9000`. With `supports_extended`, extended
+/// length fields are used instead if the command has more than 255 bytes of data or expects
+/// more than 256 bytes, and only commands with more than 65535 bytes of data are chained. Each
+/// APDU is then serialized into a buffer of 65544 bytes on the stack, instead of 261 bytes
+/// without `supports_extended`. While the card answers `61XX`, the remaining data is requested
+/// with GET RESPONSE on the logical channel of the command.
+///
+/// Successful responses are returned as `Response::Data`, all others as `Response::Status`,
+/// discarding any data sent with a warning: use [`transmit_full`] to keep it.
+pub fn transmit<T, D, const S: usize>(
+    transport: &mut T,
+    command: &CommandBuilder<D>,
+    supports_extended: bool,
+) -> Result<Response<S>, Error<T::Error>>
+where
+    T: Transmit + ?Sized,
+    D: AsRef<[u8]>,
+{
+    let (data, status) = transmit_full(transport, command, supports_extended)?;
+    Ok(match status {
+        Status::SUCCESS => Response::Data(data),
+        status => Response::Status(status),
+    })
+}
+
+/// Send `command` and return the complete response data together with the final status
+///
+/// This behaves like [`transmit`], but keeps the data sent with a warning or another
+/// non-success status. The data accumulated over the GET RESPONSE commands is capped at `S`
+/// bytes: a card sending more fails with [`Error::BufferFull`], and a card announcing more data
+/// (`61XX`) without sending any fails with [`Error::InvalidResponse`], so that a misbehaving
+/// card cannot cause an endless loop.
+pub fn transmit_full<T, D, const S: usize>(
+    transport: &mut T,
+    command: &CommandBuilder<D>,
+    supports_extended: bool,
+) -> Result<(Data<S>, Status), Error<T::Error>>
+where
+    T: Transmit + ?Sized,
+    D: AsRef<[u8]>,
+{
+    let mut reader = ResponseReader::<S>::new();
+    let mut state = match supports_extended {
+        true => {
+            send_chunks::<_, _, MAX_EXTENDED_COMMAND_LEN, S>(transport, command, true, &mut reader)?
+        }
+        false => {
+            send_chunks::<_, _, MAX_SHORT_COMMAND_LEN, S>(transport, command, false, &mut reader)?
+        }
+    };
+
+    let class = command
+        .class()
+        .channel()
+        .and_then(|channel| Class::BASIC.with_channel(channel).ok())
+        .unwrap_or(Class::BASIC);
+    while let ReadState::NeedMore(le) = state {
+        let apdu = CommandBuilder::get_response(le)
+            .with_class(class)
+            .serialize_to_heapless::<5>(false)
+            .map_err(|_| Error::BufferFull)?;
+        let response = transport.transmit(&apdu).map_err(Error::Transport)?;
+        let len = reader.data().len();
+        state = reader.push(response)?;
+        if matches!(state, ReadState::NeedMore(_)) && reader.data().len() == len {
+            return Err(Error::InvalidResponse);
+        }
+    }
+
+    let ReadState::Complete(status) = state else {
+        unreachable!("the loop only ends on a complete response")
+    };
+    Ok((reader.into_data(), status))
+}
+
+/// Send the APDUs of `command`, serialized one after the other into the same buffer of `N`
+/// bytes, and push the last response into `reader`
+///
+/// An intermediate response that is not `9000` aborts the chain and is returned as complete,
+/// without pushing it. This is not inlined, so that only the caller using the extended buffer
+/// size needs the stack space for it.
+#[inline(never)]
+fn send_chunks<T, D, const N: usize, const S: usize>(
+    transport: &mut T,
+    command: &CommandBuilder<D>,
+    supports_extended: bool,
+    reader: &mut ResponseReader<S>,
+) -> Result<ReadState, Error<T::Error>>
+where
+    T: Transmit + ?Sized,
+    D: AsRef<[u8]>,
+{
+    let mut buffer = Data::<N>::new();
+    let mut result = command.serialize_into(&mut buffer, supports_extended);
+    loop {
+        let remainder = result.map_err(|_| Error::BufferFull)?;
+        let response = transport.transmit(&buffer).map_err(Error::Transport)?;
+        let Err(remainder) = remainder else {
+            return Ok(reader.push(response)?);
+        };
+        // command chaining, the intermediate responses only consist of the status
+        let status = ResponseView::try_from(response)
+            .map_err(|_| Error::InvalidResponse)?
+            .status();
+        if status != Status::SUCCESS {
+            return Ok(ReadState::Complete(status));
+        }
+        buffer.clear();
+        result = remainder.serialize_borrowed(&mut buffer, supports_extended);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::command::{CommandView, Instruction};
+    use hex_literal::hex;
+
+    /// Replays scripted responses and records the commands
+    #[derive(Default)]
+    struct Card {
+        responses: Vec<Vec<u8>>,
+        commands: Vec<Vec<u8>>,
+        response: Vec<u8>,
+    }
+
+    impl Card {
+        fn new(responses: &[&[u8]]) -> Self {
+            Self {
+                responses: responses.iter().rev().map(|r| r.to_vec()).collect(),
+                ..Default::default()
+            }
+        }
+    }
+
+    impl Transmit for Card {
+        type Error = &'static str;
+
+        fn transmit(&mut self, command: &[u8]) -> Result<&[u8], Self::Error> {
+            self.commands.push(command.to_vec());
+            self.response = self.responses.pop().ok_or("no response")?;
+            Ok(&self.response)
+        }
+    }
+
+    fn select(data: &[u8]) -> CommandBuilder<&[u8]> {
+        CommandBuilder::new(Class::BASIC, Instruction::Select, 0x04, 0x00, data, 256)
+    }
+
+    #[test]
+    fn single() {
+        let mut card = Card::new(&[&hex!("0102 9000")]);
+        let response: Response<4> = transmit(&mut card, &select(&hex!("A000")), false).unwrap();
+        assert_eq!(
+            response,
+            Response::Data(Data::from_slice(&hex!("0102")).unwrap())
+        );
+        assert_eq!(card.commands, [hex!("00A40400 02 A000 00")]);
+
+        let mut card = Card::new(&[&hex!("6A82")]);
+        let response: Response<4> = transmit(&mut card, &select(&hex!("A000")), false).unwrap();
+        assert_eq!(response, Response::Status(Status::FILE_OR_APP_NOT_FOUND));
+
+        let mut card = Card::new(&[]);
+        assert_eq!(
+            transmit::<_, _, 4>(&mut card, &select(&[]), false),
+            Err(Error::Transport("no response"))
+        );
+        let mut card = Card::new(&[&hex!("90")]);
+        assert_eq!(
+            transmit::<_, _, 4>(&mut card, &select(&[]), false),
+            Err(Error::InvalidResponse)
+        );
+    }
+
+    #[test]
+    fn chaining() {
+        let data = [0xAB; 300];
+        let mut card = Card::new(&[&hex!("9000"), &hex!("01 9000")]);
+        let response: Response<4> = transmit(&mut card, &select(&data), false).unwrap();
+        assert_eq!(response.data(), hex!("01"));
+        assert_eq!(card.commands.len(), 2);
+        assert_eq!(card.commands[0][..5], hex!("10A40400 FF"));
+        assert_eq!(card.commands[1][..5], hex!("00A40400 2D"));
+
+        // the chain is aborted on error
+        let mut card = Card::new(&[&hex!("6884")]);
+        let response: Response<4> = transmit(&mut card, &select(&data), false).unwrap();
+        assert_eq!(
+            response,
+            Response::Status(Status::COMMAND_CHAINING_NOT_SUPPORTED)
+        );
+        assert_eq!(card.commands.len(), 1);
+    }
+
+    #[test]
+    fn extended() {
+        // with supports_extended, the command is sent as a single extended APDU
+        let data = [0xAB; 300];
+        let mut card = Card::new(&[&hex!("01 9000")]);
+        let response: Response<4> = transmit(&mut card, &select(&data), true).unwrap();
+        assert_eq!(response.data(), hex!("01"));
+        assert_eq!(card.commands.len(), 1);
+        assert_eq!(card.commands[0].len(), 4 + 3 + 300 + 2);
+        assert_eq!(card.commands[0][..7], hex!("00A40400 00012C"));
+        assert_eq!(card.commands[0][307..], hex!("0100"));
+    }
+
+    #[test]
+    fn full() {
+        let mut card = Card::new(&[&hex!("0102 6102"), &hex!("0304 6282")]);
+        let view = CommandView::try_from(&hex!("00B00000 00")[..]).unwrap();
+        let (data, status) = transmit_full::<_, _, 4>(&mut card, &view.into(), false).unwrap();
+        assert_eq!(data, hex!("01020304"));
+        assert_eq!(status, Status::UNEXPECTED_EOF);
+        assert_eq!(
+            card.commands,
+            [&hex!("00B00000 00")[..], &hex!("00C00000 02")]
+        );
+
+        let mut card = Card::new(&[&hex!("0102 6102"), &hex!("0304 9000")]);
+        assert_eq!(
+            transmit_full::<_, _, 3>(&mut card, &select(&[]), false),
+            Err(Error::BufferFull)
+        );
+    }
+
+    #[test]
+    fn get_response() {
+        let mut card = Card::new(&[&hex!("6100"), &hex!("0102 6101"), &hex!("03 9000")]);
+        let command = select(&[]).with_class(Class::try_from(0x02).unwrap());
+        let response: Response<4> = transmit(&mut card, &command, false).unwrap();
+        assert_eq!(response.data(), hex!("010203"));
+        assert_eq!(
+            card.commands,
+            [
+                &hex!("02A40400 00")[..],
+                &hex!("02C00000 00"),
+                &hex!("02C00000 01"),
+            ]
+        );
+
+        let mut card = Card::new(&[&hex!("0102 6102"), &hex!("0304 6282")]);
+        let response: Response<4> = transmit(&mut card, &select(&[]), false).unwrap();
+        assert_eq!(response, Response::Status(Status::UNEXPECTED_EOF));
+
+        let mut card = Card::new(&[&hex!("0102 6102"), &hex!("030405 9000")]);
+        assert_eq!(
+            transmit::<_, _, 4>(&mut card, &select(&[]), false),
+            Err(Error::BufferFull)
+        );
+
+        // a card announcing more data without sending any is rejected
+        let mut card = Card::new(&[&hex!("6102"), &hex!("6102"), &hex!("6102")]);
+        assert_eq!(
+            transmit::<_, _, 4>(&mut card, &select(&[]), false),
+            Err(Error::InvalidResponse)
+        );
+        assert_eq!(card.commands.len(), 2);
+    }
+}
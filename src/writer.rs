@@ -0,0 +1,389 @@
+//! Output abstraction used to serialize APDUs without allocating
+
+/// Error returned when a [`Writer`] cannot accept any more data
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct BufferFull;
+
+impl core::fmt::Display for BufferFull {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("buffer full")
+    }
+}
+
+impl core::error::Error for BufferFull {}
+
+/// Sink for serialized APDUs
+pub trait Writer {
+    type Error: From<BufferFull>;
+
+    /// Write as many bytes of `data` as possible, returning the number of bytes written
+    fn write(&mut self, data: &[u8]) -> Result<usize, Self::Error>;
+
+    /// Number of bytes that can still be written
+    fn remaining_len(&self) -> usize;
+
+    /// Write all of `data`, failing with [`BufferFull`] if the writer stops accepting bytes
+    fn write_all(&mut self, mut data: &[u8]) -> Result<(), Self::Error> {
+        while !data.is_empty() {
+            let written = self.write(data)?;
+            if written == 0 {
+                return Err(BufferFull.into());
+            }
+            data = &data[written..];
+        }
+        Ok(())
+    }
+}
+
+/// Asynchronous version of [`Writer`]
+#[cfg(feature = "async")]
+#[allow(async_fn_in_trait)]
+pub trait AsyncWriter {
+    type Error: From<BufferFull>;
+
+    /// Write as many bytes of `data` as possible, returning the number of bytes written
+    async fn write(&mut self, data: &[u8]) -> Result<usize, Self::Error>;
+
+    /// Number of bytes that can still be written
+    fn remaining_len(&self) -> usize;
+
+    /// Write all of `data`, failing with [`BufferFull`] if the writer stops accepting bytes
+    async fn write_all(&mut self, mut data: &[u8]) -> Result<(), Self::Error> {
+        while !data.is_empty() {
+            let written = self.write(data).await?;
+            if written == 0 {
+                return Err(BufferFull.into());
+            }
+            data = &data[written..];
+        }
+        Ok(())
+    }
+}
+
+impl<W: Writer + ?Sized> Writer for &mut W {
+    type Error = W::Error;
+
+    fn write(&mut self, data: &[u8]) -> Result<usize, Self::Error> {
+        (**self).write(data)
+    }
+
+    fn remaining_len(&self) -> usize {
+        (**self).remaining_len()
+    }
+}
+
+/// [`Writer`] filling a byte slice from the start
+#[derive(Debug)]
+pub struct SliceWriter<'a> {
+    buffer: &'a mut [u8],
+    written: usize,
+}
+
+impl<'a> SliceWriter<'a> {
+    pub fn new(buffer: &'a mut [u8]) -> Self {
+        Self { buffer, written: 0 }
+    }
+
+    /// Number of bytes written so far
+    pub fn len(&self) -> usize {
+        self.written
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.written == 0
+    }
+
+    /// The bytes written so far
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buffer[..self.written]
+    }
+
+    /// The bytes written so far, with the lifetime of the underlying buffer
+    pub fn into_slice(self) -> &'a [u8] {
+        &self.buffer[..self.written]
+    }
+}
+
+impl Writer for SliceWriter<'_> {
+    type Error = BufferFull;
+
+    fn write(&mut self, data: &[u8]) -> Result<usize, Self::Error> {
+        let len = data.len().min(self.remaining_len());
+        self.buffer[self.written..][..len].copy_from_slice(&data[..len]);
+        self.written += len;
+        Ok(len)
+    }
+
+    fn remaining_len(&self) -> usize {
+        self.buffer.len() - self.written
+    }
+}
+
+/// [`Writer`] backed by an owned array of `N` bytes
+///
+/// Writes are truncated to the space left, so [`Writer::write_all`] fails with [`BufferFull`]
+/// once `N` bytes have been written.
+#[derive(Clone, Debug)]
+pub struct BufWriter<const N: usize> {
+    buffer: [u8; N],
+    written: usize,
+}
+
+impl<const N: usize> BufWriter<N> {
+    pub const fn new() -> Self {
+        Self {
+            buffer: [0; N],
+            written: 0,
+        }
+    }
+
+    /// Number of bytes written so far
+    pub fn len(&self) -> usize {
+        self.written
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.written == 0
+    }
+
+    /// The bytes written so far
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buffer[..self.written]
+    }
+
+    /// Discard the bytes written so far, making the full capacity available again
+    pub fn clear(&mut self) {
+        self.written = 0;
+    }
+}
+
+impl<const N: usize> Default for BufWriter<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Writer for BufWriter<N> {
+    type Error = BufferFull;
+
+    fn write(&mut self, data: &[u8]) -> Result<usize, Self::Error> {
+        let len = data.len().min(self.remaining_len());
+        self.buffer[self.written..][..len].copy_from_slice(&data[..len]);
+        self.written += len;
+        Ok(len)
+    }
+
+    fn remaining_len(&self) -> usize {
+        N - self.written
+    }
+}
+
+impl<const N: usize> Writer for heapless::Vec<u8, N> {
+    type Error = BufferFull;
+
+    fn write(&mut self, data: &[u8]) -> Result<usize, Self::Error> {
+        let len = data.len().min(self.remaining_len());
+        // cannot fail, the length was checked above
+        self.extend_from_slice(&data[..len]).ok();
+        Ok(len)
+    }
+
+    fn remaining_len(&self) -> usize {
+        N - self.len()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Writer for alloc::vec::Vec<u8> {
+    type Error = BufferFull;
+
+    fn write(&mut self, data: &[u8]) -> Result<usize, Self::Error> {
+        self.extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn remaining_len(&self) -> usize {
+        usize::MAX
+    }
+}
+
+/// [`Writer`] adapter for any [`std::io::Write`]
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct IoWriter<W>(pub W);
+
+#[cfg(feature = "std")]
+impl<W> IoWriter<W> {
+    pub fn into_inner(self) -> W {
+        self.0
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<BufferFull> for std::io::Error {
+    fn from(_: BufferFull) -> Self {
+        std::io::ErrorKind::WriteZero.into()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> Writer for IoWriter<W> {
+    type Error = std::io::Error;
+
+    fn write(&mut self, data: &[u8]) -> Result<usize, Self::Error> {
+        // retry like `std::io::Write::write_all` does
+        loop {
+            match self.0.write(data) {
+                Err(error) if error.kind() == std::io::ErrorKind::Interrupted => continue,
+                result => return result,
+            }
+        }
+    }
+
+    fn remaining_len(&self) -> usize {
+        usize::MAX
+    }
+}
+
+/// [`Writer`] adapter writing each byte as two uppercase hex digits, separated by spaces
+pub(crate) struct HexWriter<'a, W: ?Sized> {
+    writer: &'a mut W,
+    first: bool,
+}
+
+impl<'a, W: Writer + ?Sized> HexWriter<'a, W> {
+    pub(crate) fn new(writer: &'a mut W) -> Self {
+        Self {
+            writer,
+            first: true,
+        }
+    }
+}
+
+impl<W: Writer + ?Sized> Writer for HexWriter<'_, W> {
+    type Error = W::Error;
+
+    fn write(&mut self, data: &[u8]) -> Result<usize, Self::Error> {
+        const DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+        for &byte in data {
+            let digits = [
+                DIGITS[usize::from(byte >> 4)],
+                DIGITS[usize::from(byte & 0x0F)],
+            ];
+            if !self.first {
+                self.writer.write_all(b" ")?;
+            }
+            self.writer.write_all(&digits)?;
+            self.first = false;
+        }
+        Ok(data.len())
+    }
+
+    fn remaining_len(&self) -> usize {
+        // the first byte does not need a separator
+        (self.writer.remaining_len() + usize::from(self.first)) / 3
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hex_writer() {
+        let mut buffer = heapless::Vec::<u8, 16>::new();
+        let mut writer = HexWriter::new(&mut buffer);
+        assert_eq!(writer.remaining_len(), 5);
+        writer.write_all(&[0x00, 0xA4]).unwrap();
+        writer.write_all(&[]).unwrap();
+        writer.write_all(&[0x0F]).unwrap();
+        assert_eq!(writer.remaining_len(), 2);
+        assert_eq!(buffer, b"00 A4 0F");
+
+        let mut buffer = heapless::Vec::<u8, 4>::new();
+        assert_eq!(
+            HexWriter::new(&mut buffer).write_all(&[1, 2]),
+            Err(BufferFull)
+        );
+    }
+
+    #[test]
+    fn slice_writer() {
+        let mut buffer = [0; 5];
+        let mut writer = SliceWriter::new(&mut buffer);
+        assert!(writer.is_empty());
+        assert_eq!(writer.remaining_len(), 5);
+        assert_eq!(writer.write(&[1, 2, 3]), Ok(3));
+        assert_eq!(writer.remaining_len(), 2);
+        assert_eq!(writer.write(&[4, 5, 6]), Ok(2));
+        assert_eq!(writer.write(&[7]), Ok(0));
+        assert_eq!(writer.as_slice(), &[1, 2, 3, 4, 5]);
+        assert_eq!(writer.write_all(&[7]), Err(BufferFull));
+        assert_eq!(writer.into_slice(), &[1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn buf_writer() {
+        let mut writer = BufWriter::<5>::new();
+        assert!(writer.is_empty());
+        assert_eq!(writer.remaining_len(), 5);
+        assert_eq!(writer.write(&[1, 2, 3]), Ok(3));
+        assert_eq!(writer.len(), 3);
+        assert_eq!(writer.remaining_len(), 2);
+        assert_eq!(writer.write(&[4, 5, 6]), Ok(2));
+        assert_eq!(writer.write(&[7]), Ok(0));
+        assert_eq!(writer.remaining_len(), 0);
+        assert_eq!(writer.write_all(&[7]), Err(BufferFull));
+        assert_eq!(writer.as_slice(), &[1, 2, 3, 4, 5]);
+
+        writer.clear();
+        assert!(writer.is_empty());
+        assert_eq!(writer.remaining_len(), 5);
+        writer.write_all(&[8, 9]).unwrap();
+        assert_eq!(writer.as_slice(), &[8, 9]);
+    }
+
+    #[test]
+    fn heapless_writer() {
+        let mut writer = heapless::Vec::<u8, 5>::new();
+        assert_eq!(writer.remaining_len(), 5);
+        assert_eq!(writer.write(&[1, 2, 3]), Ok(3));
+        assert_eq!(writer.remaining_len(), 2);
+        assert_eq!(writer.write(&[4, 5, 6]), Ok(2));
+        assert_eq!(writer.remaining_len(), 0);
+        assert_eq!(writer.write_all(&[7]), Err(BufferFull));
+        assert_eq!(writer, [1, 2, 3, 4, 5]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn io_writer() {
+        let mut writer = IoWriter(std::io::Cursor::new([0; 3]));
+        assert_eq!(writer.remaining_len(), usize::MAX);
+        writer.write_all(&[1, 2]).unwrap();
+        assert_eq!(
+            writer.write_all(&[3, 4]).unwrap_err().kind(),
+            std::io::ErrorKind::WriteZero
+        );
+        assert_eq!(writer.into_inner().into_inner(), [1, 2, 3]);
+
+        /// Fails with `Interrupted` on every other call
+        struct Interrupting(Vec<u8>, bool);
+        impl std::io::Write for Interrupting {
+            fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+                self.1 = !self.1;
+                if self.1 {
+                    return Err(std::io::ErrorKind::Interrupted.into());
+                }
+                self.0.push(data[0]);
+                Ok(1)
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        let mut writer = IoWriter(Interrupting(Vec::new(), false));
+        writer.write_all(&[1, 2, 3]).unwrap();
+        assert_eq!(writer.into_inner().0, [1, 2, 3]);
+    }
+}